@@ -1,15 +1,10 @@
-use std::sync::Arc;
-use wgpu::include_wgsl;
-use wgpu::util::DeviceExt;
-use winit::application::ApplicationHandler;
-use winit::dpi::PhysicalSize;
-use winit::event::{ElementState, KeyEvent, WindowEvent};
-use winit::event_loop::{ActiveEventLoop, ControlFlow, EventLoop};
-use winit::keyboard::{KeyCode, PhysicalKey};
-use winit::window::{Window, WindowId};
+use wgpu_template::{
+    hot_wgsl, Application, Frame, GpuContext, HotShader, Restorable, TrackedBuffer,
+};
+use winit::window::{Window, WindowAttributes};
 
 pub fn main() -> Result<(), impl std::error::Error> {
-    run()
+    wgpu_template::run::<Example>()
 }
 
 #[repr(C)]
@@ -20,7 +15,8 @@ struct Vertex {
 }
 
 impl Vertex {
-    const ATTRIBS: [wgpu::VertexAttribute; 2] = wgpu::vertex_attr_array![0 => Float32x3, 1 => Float32x3];
+    const ATTRIBS: [wgpu::VertexAttribute; 2] =
+        wgpu::vertex_attr_array![0 => Float32x3, 1 => Float32x3];
 
     fn desc() -> wgpu::VertexBufferLayout<'static> {
         // wgpu::VertexBufferLayout {
@@ -69,151 +65,63 @@ impl Vertex {
 // Triangle 👆
 
 const VERTICES: &[Vertex] = &[
-    Vertex { position: [-0.0868241, 0.49240386, 0.0], color: [0.5, 0.0, 0.0] }, // A
-    Vertex { position: [-0.49513406, 0.06958647, 0.0], color: [0.0, 0.5, 0.0] }, // B
-    Vertex { position: [-0.21918549, -0.44939706, 0.0], color: [0.0, 0.0, 0.5] }, // C
-    Vertex { position: [0.35966998, -0.3473291, 0.0], color: [0.0, 0.5, 0.0] }, // D
-    Vertex { position: [0.44147372, 0.2347359, 0.0], color: [0.5, 0.0, 0.0] }, // E
+    Vertex {
+        position: [-0.0868241, 0.49240386, 0.0],
+        color: [0.5, 0.0, 0.0],
+    }, // A
+    Vertex {
+        position: [-0.49513406, 0.06958647, 0.0],
+        color: [0.0, 0.5, 0.0],
+    }, // B
+    Vertex {
+        position: [-0.21918549, -0.44939706, 0.0],
+        color: [0.0, 0.0, 0.5],
+    }, // C
+    Vertex {
+        position: [0.35966998, -0.3473291, 0.0],
+        color: [0.0, 0.5, 0.0],
+    }, // D
+    Vertex {
+        position: [0.44147372, 0.2347359, 0.0],
+        color: [0.5, 0.0, 0.0],
+    }, // E
 ];
 
-const INDICES: &[u16] = &[
-    0, 1, 4,
-    1, 2, 4,
-    2, 3, 4,
-];
+const INDICES: &[u16] = &[0, 1, 4, 1, 2, 4, 2, 3, 4];
 // Polygon 👆
 
-#[derive(Default)]
-struct App {
-    window: Option<Arc<Window>>,
-    state: Option<State>,
-    scale_factor: f64,
-}
-
-impl App {
-    fn input(&mut self, event: &WindowEvent) -> bool {
-        false
-    }
-
-    fn exit(&mut self, event_loop: &ActiveEventLoop) {
-        println!("Exit App !");
-
-        // https://github.com/rust-windowing/winit/issues/3668
-        // Use Option::take to Dispose Option<Arc<Window>>
-        self.window.take();
-        self.state.take();
-        event_loop.exit();
-    }
-}
-
-struct State {
-    surface: wgpu::Surface<'static>,
-    device: wgpu::Device,
-    queue: wgpu::Queue,
-    config: wgpu::SurfaceConfiguration,
-    size: winit::dpi::PhysicalSize<u32>,
+pub struct Example {
     background_color: wgpu::Color,
-    render_pipeline: wgpu::RenderPipeline,
-    vertex_buffer: wgpu::Buffer,
-    index_buffer: wgpu::Buffer,
+    /// `shader.wgsl`, reloaded when saved with `--hot-reload`.
+    shader: HotShader,
+    render_pipeline_layout: Restorable<wgpu::PipelineLayout>,
+    render_pipeline: Restorable<wgpu::RenderPipeline>,
+    vertex_buffer: Restorable<TrackedBuffer>,
+    index_buffer: Restorable<TrackedBuffer>,
     num_indices: u32,
 }
 
-impl State {
-    async fn new(window: Arc<Window>) -> Self {
-        let size = window.inner_size();
-
-        let instance = wgpu::Instance::new(wgpu::InstanceDescriptor {
-            backends: wgpu::Backends::PRIMARY,
-            ..Default::default()
-        });
-
-        let surface = instance.create_surface(window).unwrap();
-
-        let adapter = instance
-            .request_adapter(&wgpu::RequestAdapterOptions {
-                power_preference: wgpu::PowerPreference::default(),
-                compatible_surface: Some(&surface),
-                force_fallback_adapter: false,
-            })
-            .await
-            .unwrap();
-
-        let (device, queue) = adapter
-            .request_device(
-                &wgpu::DeviceDescriptor {
-                    required_features: wgpu::Features::empty(),
-                    required_limits: wgpu::Limits::default(),
-                    label: None,
-                },
-                None,
-            )
-            .await
-            .unwrap();
-
-        let surface_caps = surface.get_capabilities(&adapter);
-
-        let surface_format = surface_caps
-            .formats
-            .iter()
-            .copied()
-            .filter(|f| f.is_srgb())
-            .next()
-            .unwrap_or(surface_caps.formats[0]);
-
-        let modes = &surface_caps.present_modes;
-        println!("Available present_modes: {modes:?}");
-        println!("Available surface_formats: {:?}", &surface_caps.formats);
-        println!("Current surface_format: {:?}", surface_format);
-
-        let config = wgpu::SurfaceConfiguration {
-            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
-            format: surface_format,
-            width: size.width,
-            height: size.height,
-            present_mode: surface_caps.present_modes[0],
-            alpha_mode: surface_caps.alpha_modes[0],
-            view_formats: vec![],
-            desired_maximum_frame_latency: 2,
-        };
-
-        let background_color = wgpu::Color {
-            r: 0.0,
-            g: 0.0,
-            b: 0.0,
-            a: 1.0,
-        };
-
-        // let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
-        //     label: Some("Shader"),
-        //     source: wgpu::ShaderSource::Wgsl(include_str!("shader.wgsl").into()),
-        // });
-        // Same as 👆
-        let shader = device.create_shader_module(include_wgsl!("shader.wgsl"));
-
-        let render_pipeline_layout =
-            device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
-                label: Some("Render Pipeline Layout"),
-                bind_group_layouts: &[],
-                push_constant_ranges: &[],
-            });
-
-        let render_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+impl Example {
+    /// Builds the pipeline for `shader`, again each time it is reloaded.
+    fn create_render_pipeline(
+        ctx: &GpuContext,
+        layout: &wgpu::PipelineLayout,
+        shader: &wgpu::ShaderModule,
+    ) -> wgpu_template::Result<Restorable<wgpu::RenderPipeline>> {
+        ctx.try_create_render_pipeline(&wgpu::RenderPipelineDescriptor {
             label: Some("Render Pipeline"),
-            layout: Some(&render_pipeline_layout),
+            layout: Some(layout),
             vertex: wgpu::VertexState {
-                module: &shader,
+                module: shader,
                 entry_point: "vs_main",
-                buffers: &[
-                    Vertex::desc()
-                ],
+                buffers: &[Vertex::desc()],
                 compilation_options: wgpu::PipelineCompilationOptions::default(),
             },
             fragment: Some(wgpu::FragmentState {
-                module: &shader,
+                module: shader,
                 entry_point: "fs_main",
                 targets: &[Some(wgpu::ColorTargetState {
-                    format: config.format,
+                    format: ctx.format,
                     blend: Some(wgpu::BlendState::REPLACE),
                     write_mask: wgpu::ColorWrites::ALL,
                 })],
@@ -235,15 +143,43 @@ impl State {
                 alpha_to_coverage_enabled: false,
             },
             multiview: None,
-        });
+        })
+    }
+}
+
+impl Application for Example {
+    fn init(ctx: &GpuContext) -> wgpu_template::Result<Self> {
+        let background_color = wgpu::Color {
+            r: 0.0,
+            g: 0.0,
+            b: 0.0,
+            a: 1.0,
+        };
+
+        // let shader = ctx.try_create_shader_module(wgpu::ShaderModuleDescriptor {
+        //     label: Some("Shader"),
+        //     source: wgpu::ShaderSource::Wgsl(include_str!("shader.wgsl").into()),
+        // })?;
+        // Same as 👆, except that with `--hot-reload` the file is read from disk and watched.
+        let shader = HotShader::new(ctx, hot_wgsl!("shader.wgsl"))?;
+
+        let render_pipeline_layout =
+            ctx.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some("Render Pipeline Layout"),
+                bind_group_layouts: &[],
+                push_constant_ranges: &[],
+            })?;
 
-        let vertex_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+        let render_pipeline =
+            Self::create_render_pipeline(ctx, &render_pipeline_layout.get(), &shader.module())?;
+
+        let vertex_buffer = ctx.create_buffer_init(&wgpu::util::BufferInitDescriptor {
             label: Some("Vertex Buffer"),
             contents: bytemuck::cast_slice(VERTICES),
             usage: wgpu::BufferUsages::VERTEX,
         });
 
-        let index_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+        let index_buffer = ctx.create_buffer_init(&wgpu::util::BufferInitDescriptor {
             label: Some("Index Buffer"),
             contents: bytemuck::cast_slice(INDICES),
             usage: wgpu::BufferUsages::INDEX,
@@ -251,174 +187,62 @@ impl State {
 
         let num_indices = INDICES.len() as u32;
 
-        Self {
-            surface,
-            device,
-            queue,
-            config,
-            size,
+        Ok(Self {
             background_color,
+            shader,
+            render_pipeline_layout,
             render_pipeline,
             vertex_buffer,
             index_buffer,
             num_indices,
-        }
+        })
     }
 
-    fn resize(&mut self, new_size: winit::dpi::PhysicalSize<u32>) {
-        if new_size.width > 0 && new_size.height > 0 {
-            self.size = new_size;
-            self.config.width = new_size.width;
-            self.config.height = new_size.height;
-            self.surface.configure(&self.device, &self.config);
-            let _ = self.render();
-        }
+    fn window_attributes() -> WindowAttributes {
+        Window::default_attributes()
+            .with_title("wgpu-template")
+            .with_inner_size(winit::dpi::LogicalSize::new(320.0, 280.0))
     }
 
-    fn update(&mut self) {
-        // todo!()
+    fn reload_shaders(&mut self, ctx: &GpuContext) {
+        let layout = self.render_pipeline_layout.get();
+        let rebuilt = self.shader.reload_with(ctx, |shader| {
+            Self::create_render_pipeline(ctx, &layout, shader)
+        });
+        if let Some(render_pipeline) = rebuilt {
+            self.render_pipeline = render_pipeline;
+        }
     }
 
-    fn render(&mut self) -> Result<(), wgpu::SurfaceError> {
-        let output = self.surface.get_current_texture()?;
-
-        let view = output
-            .texture
-            .create_view(&wgpu::TextureViewDescriptor::default());
-
-        // The `encoder` builds a command buffer that we can then send to the GPU.
-        let mut encoder = self
-            .device
-            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
-                label: Some("Render Encoder"),
+    fn render(&mut self, frame: &mut Frame) {
+        let render_pipeline = self.render_pipeline.get();
+        let vertex_buffer = self.vertex_buffer.get();
+        let index_buffer = self.index_buffer.get();
+
+        let mut render_pass = frame
+            .encoder
+            .begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Render Pass"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: frame.view,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(self.background_color),
+                        store: wgpu::StoreOp::Store,
+                    },
+                })],
+                depth_stencil_attachment: None,
+                occlusion_query_set: None,
+                timestamp_writes: frame.profiler.render_pass_timestamps("Render Pass"),
             });
 
-        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
-            label: Some("Render Pass"),
-            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                view: &view,
-                resolve_target: None,
-                ops: wgpu::Operations {
-                    load: wgpu::LoadOp::Clear(self.background_color),
-                    store: wgpu::StoreOp::Store,
-                },
-            })],
-            depth_stencil_attachment: None,
-            occlusion_query_set: None,
-            timestamp_writes: None,
-        });
-
-        render_pass.set_pipeline(&self.render_pipeline);
-        render_pass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
-        render_pass.set_index_buffer(self.index_buffer.slice(..), wgpu::IndexFormat::Uint16);
+        render_pass.set_pipeline(&render_pipeline);
+        render_pass.set_vertex_buffer(0, vertex_buffer.slice(..));
+        render_pass.set_index_buffer(index_buffer.slice(..), wgpu::IndexFormat::Uint16);
 
         render_pass.draw_indexed(0..self.num_indices, 0, 0..1);
 
         // begin_render_pass() borrows encoder mutably (aka &mut self). We can't call encoder.finish() until we release that mutable borrow.
         drop(render_pass);
-
-        self.queue.submit(std::iter::once(encoder.finish()));
-        output.present();
-
-        Ok(())
-    }
-}
-
-impl ApplicationHandler for App {
-    fn resumed(&mut self, event_loop: &ActiveEventLoop) {
-        println!("App Resumed !");
-
-        // Initialized at first Resumed Event
-        if self.window.is_none() {
-            println!("Create Window !");
-            let window_attributes = Window::default_attributes()
-                .with_title("wgpu-template")
-                .with_inner_size(winit::dpi::LogicalSize::new(320.0, 280.0));
-
-            let window = Arc::new(event_loop.create_window(window_attributes).unwrap());
-            let state = pollster::block_on(State::new(Arc::clone(&window)));
-            println!("Bind Window !");
-
-            self.scale_factor = window.scale_factor();
-            self.window = Some(window);
-            self.state = Some(state);
-            self.window.as_ref().unwrap().pre_present_notify();
-            self.window.as_ref().unwrap().request_redraw();
-        }
-    }
-
-    fn suspended(&mut self, event_loop: &ActiveEventLoop) {
-        println!("App Suspended !");
     }
-
-    fn new_events(&mut self, event_loop: &ActiveEventLoop, cause: winit::event::StartCause) {
-        // println!("new_events: {cause:?}");
-    }
-
-    fn window_event(
-        &mut self,
-        event_loop: &ActiveEventLoop,
-        _window_id: WindowId,
-        event: WindowEvent,
-    ) {
-        if self.input(&event) {
-            return;
-        }
-        // println!("WindowEvent: {event:?}");
-        match event {
-            WindowEvent::CloseRequested => self.exit(event_loop),
-            WindowEvent::KeyboardInput {
-                event:
-                    KeyEvent {
-                        physical_key,
-                        state: ElementState::Pressed,
-                        ..
-                    },
-                ..
-            } => match physical_key {
-                PhysicalKey::Code(KeyCode::Escape) => self.exit(event_loop),
-                _ => (),
-            },
-            WindowEvent::RedrawRequested => {
-                let state = self.state.as_mut().unwrap();
-                state.update();
-                match state.render() {
-                    Ok(_) => {}
-                    Err(e) => {
-                        eprintln!("{:?}", e);
-                        match e {
-                            wgpu::SurfaceError::Lost => state.resize(state.size),
-                            wgpu::SurfaceError::OutOfMemory => self.exit(event_loop),
-                            _ => (),
-                        }
-                    }
-                }
-            }
-            WindowEvent::Resized(physical_size) => {
-                println!("On Resized !");
-                self.state.as_mut().unwrap().resize(physical_size);
-            }
-            WindowEvent::ScaleFactorChanged {
-                scale_factor,
-                mut inner_size_writer,
-            } => {
-                let PhysicalSize { width, height } = self.state.as_ref().unwrap().size;
-                let new_width = width as f64 / self.scale_factor * scale_factor;
-                let new_height = height as f64 / self.scale_factor * scale_factor;
-                let new_inner_size =
-                    PhysicalSize::new(new_width.floor() as u32, new_height.floor() as u32);
-                let _ = inner_size_writer.request_inner_size(new_inner_size);
-                println!("Request new size: {new_inner_size:?}");
-            }
-            _ => (),
-        }
-    }
-}
-
-pub fn run() -> Result<(), winit::error::EventLoopError> {
-    let mut app = App::default();
-    let event_loop = EventLoop::new().unwrap(); 
-
-    event_loop.set_control_flow(ControlFlow::Poll);
-    event_loop.run_app(&mut app)
 }
@@ -1,22 +1,14 @@
-mod texture;
-mod camera;
-
-use camera::*;
-use cgmath::{prelude::*, Matrix4, Point3, Quaternion, Vector3};
-use texture::Texture;
-use std::sync::Arc;
-use std::vec;
-use wgpu::include_wgsl;
-use wgpu::util::DeviceExt;
-use winit::application::ApplicationHandler;
-use winit::dpi::PhysicalSize;
-use winit::event::{ElementState, KeyEvent, WindowEvent};
-use winit::event_loop::{ActiveEventLoop, ControlFlow, EventLoop};
-use winit::keyboard::{KeyCode, PhysicalKey};
-use winit::window::{Window, WindowId};
+use cgmath::{prelude::*, Matrix4, Quaternion, Vector3};
+use std::time::Duration;
+use wgpu_template::camera::{Camera, CameraController, CameraUniform, OrbitController, Projection};
+use wgpu_template::texture::{self, Texture, TextureOptions};
+use wgpu_template::{
+    hot_wgsl, Application, Frame, GpuContext, HotShader, RedrawPolicy, Restorable, TrackedBuffer,
+};
+use winit::window::{Window, WindowAttributes};
 
 pub fn main() -> Result<(), impl std::error::Error> {
-    run()
+    wgpu_template::run::<Example>()
 }
 
 #[repr(C)]
@@ -111,132 +103,103 @@ impl InstanceRaw {
                     shader_location: 8,
                     format: wgpu::VertexFormat::Float32x4,
                 },
-            ]
+            ],
         }
     }
 }
 
 const NUM_INSTANCES_PER_ROW: u32 = 10;
-const INSTANCE_DISPLACEMENT: Vector3<f32> = Vector3::new(NUM_INSTANCES_PER_ROW as f32 * 0.5, 0.0, NUM_INSTANCES_PER_ROW as f32 * 0.5);
-
-#[derive(Default)]
-struct App {
-    window: Option<Arc<Window>>,
-    state: Option<State>,
-    scale_factor: f64,
-}
-
-impl App {
-    fn input(&mut self, event: &WindowEvent) -> bool {
-        self.state.as_mut().unwrap().camera_controller.process_events(event);
-        false
-    }
-
-    fn exit(&mut self, event_loop: &ActiveEventLoop) {
-        println!("Exit App !");
-
-        // https://github.com/rust-windowing/winit/issues/3668
-        // Use Option::take to Dispose Option<Arc<Window>>
-        self.window.take();
-        self.state.take();
-        event_loop.exit();
-    }
-}
+const INSTANCE_DISPLACEMENT: Vector3<f32> = Vector3::new(
+    NUM_INSTANCES_PER_ROW as f32 * 0.5,
+    0.0,
+    NUM_INSTANCES_PER_ROW as f32 * 0.5,
+);
 
-
-struct State {
-    surface: wgpu::Surface<'static>,
-    device: wgpu::Device,
-    queue: wgpu::Queue,
-    config: wgpu::SurfaceConfiguration,
-    size: winit::dpi::PhysicalSize<u32>,
+pub struct Example {
     background_color: wgpu::Color,
-    render_pipeline: wgpu::RenderPipeline,
-    vertex_buffer: wgpu::Buffer,
-    index_buffer: wgpu::Buffer,
+    /// `shader.wgsl`, reloaded when saved with `--hot-reload`.
+    shader: HotShader,
+    render_pipeline_layout: Restorable<wgpu::PipelineLayout>,
+    render_pipeline: Restorable<wgpu::RenderPipeline>,
+    vertex_buffer: Restorable<TrackedBuffer>,
+    index_buffer: Restorable<TrackedBuffer>,
     num_indices: u32,
-    diffuse_bind_group: wgpu::BindGroup,
-    diffuse_texture: texture::Texture,
+    diffuse_bind_group: Restorable<wgpu::BindGroup>,
+    #[allow(dead_code)]
+    diffuse_texture: Restorable<texture::Texture>,
     camera: Camera,
+    /// The camera before the last update, to interpolate from when rendering.
+    previous_camera: Camera,
     camera_uniform: CameraUniform,
-    camera_buffer: wgpu::Buffer,
-    camera_bind_group: wgpu::BindGroup,
-    camera_controller: CameraController,
+    camera_buffer: Restorable<TrackedBuffer>,
+    camera_bind_group: Restorable<wgpu::BindGroup>,
+    camera_controller: Box<dyn CameraController>,
     instances: Vec<Instance>,
-    instance_buffer: wgpu::Buffer,
-    depth_texture: Texture,
+    instance_buffer: Restorable<TrackedBuffer>,
+    depth_texture: Restorable<Texture>,
 }
 
-impl State {
-    async fn new(window: Arc<Window>) -> Self {
-        let size = window.inner_size();
-
-        let instance = wgpu::Instance::new(wgpu::InstanceDescriptor {
-            backends: wgpu::Backends::PRIMARY,
-            ..Default::default()
-        });
-
-        // Surface
-
-        let surface = instance.create_surface(window).unwrap();
-
-        let adapter = instance
-            .request_adapter(&wgpu::RequestAdapterOptions {
-                power_preference: wgpu::PowerPreference::default(),
-                compatible_surface: Some(&surface),
-                force_fallback_adapter: false,
-            })
-            .await
-            .unwrap();
-
-        let (device, queue) = adapter
-            .request_device(
-                &wgpu::DeviceDescriptor {
-                    required_features: wgpu::Features::empty(),
-                    required_limits: wgpu::Limits::default(),
-                    label: None,
-                },
-                None,
-            )
-            .await
-            .unwrap();
-
-        let surface_caps = surface.get_capabilities(&adapter);
-
-        let surface_format = surface_caps
-            .formats
-            .iter()
-            .copied()
-            .filter(|f| f.is_srgb())
-            .next()
-            .unwrap_or(surface_caps.formats[0]);
-
-        let modes = &surface_caps.present_modes;
-        println!("Available present_modes: {modes:?}");
-        println!("Available surface_formats: {:?}", &surface_caps.formats);
-        println!("Current surface_format: {:?}", surface_format);
-
-        let config = wgpu::SurfaceConfiguration {
-            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
-            format: surface_format,
-            width: size.width,
-            height: size.height,
-            present_mode: surface_caps.present_modes[0],
-            alpha_mode: surface_caps.alpha_modes[0],
-            view_formats: vec![],
-            desired_maximum_frame_latency: 2,
-        };
-
-        surface.configure(&device, &config);
+impl Example {
+    /// Builds the pipeline for `shader`, again each time it is reloaded.
+    fn create_render_pipeline(
+        ctx: &GpuContext,
+        layout: &wgpu::PipelineLayout,
+        shader: &wgpu::ShaderModule,
+    ) -> wgpu_template::Result<Restorable<wgpu::RenderPipeline>> {
+        ctx.try_create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Render Pipeline"),
+            layout: Some(layout),
+            vertex: wgpu::VertexState {
+                module: shader,
+                entry_point: "vs_main",
+                buffers: &[Vertex::desc(), InstanceRaw::desc()],
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: shader,
+                entry_point: "fs_main",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: ctx.format,
+                    blend: Some(wgpu::BlendState::REPLACE),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                strip_index_format: None,
+                front_face: wgpu::FrontFace::Ccw,
+                cull_mode: Some(wgpu::Face::Back),
+                polygon_mode: wgpu::PolygonMode::Fill,
+                unclipped_depth: false,
+                conservative: false,
+            },
+            depth_stencil: Some(wgpu::DepthStencilState {
+                format: texture::Texture::DEPTH_FORMAT,
+                depth_write_enabled: true,
+                depth_compare: wgpu::CompareFunction::Less,
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState::default(),
+            }),
+            multisample: wgpu::MultisampleState {
+                count: 1,
+                mask: !0,
+                alpha_to_coverage_enabled: false,
+            },
+            multiview: None,
+        })
+    }
+}
 
+impl Application for Example {
+    fn init(ctx: &GpuContext) -> wgpu_template::Result<Self> {
         // Texture
 
         let diffuse_bytes = include_bytes!("smile.png");
-        let diffuse_texture =
-            texture::Texture::from_bytes(&device, &queue, diffuse_bytes, "smile_png").unwrap();
+        let diffuse_texture = ctx.create_texture_from_bytes(diffuse_bytes, "smile_png")?;
 
         let texture_bind_group_layout =
-            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            ctx.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
                 label: Some("texture_bind_group_layout"),
                 entries: &[
                     wgpu::BindGroupLayoutEntry {
@@ -258,20 +221,20 @@ impl State {
                 ],
             });
 
-        let diffuse_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+        let diffuse_bind_group = ctx.create_bind_group(&wgpu::BindGroupDescriptor {
             label: Some("diffuse_bind_group"),
-            layout: &texture_bind_group_layout,
+            layout: &texture_bind_group_layout.get(),
             entries: &[
                 wgpu::BindGroupEntry {
                     binding: 0,
-                    resource: wgpu::BindingResource::TextureView(&diffuse_texture.view),
+                    resource: wgpu::BindingResource::TextureView(&diffuse_texture.get().view),
                 },
                 wgpu::BindGroupEntry {
                     binding: 1,
-                    resource: wgpu::BindingResource::Sampler(&diffuse_texture.sampler),
+                    resource: wgpu::BindingResource::Sampler(&diffuse_texture.get().sampler),
                 },
             ],
-        });
+        })?;
 
         let background_color = wgpu::Color {
             r: 0.0,
@@ -286,23 +249,25 @@ impl State {
             eye: (0.0, 1.0, 2.0).into(),
             target: (0.0, 0.0, 0.0).into(),
             up: Vector3::unit_y(),
-            aspect: config.width as f32 / config.height as f32,
-            fovy: 45.0,
-            znear: 0.1,
-            zfar: 100.0,
+            projection: Projection::Perspective {
+                aspect: ctx.size.width as f32 / ctx.size.height as f32,
+                fovy: 45.0,
+                znear: 0.1,
+                zfar: 100.0,
+            },
         };
 
         let mut camera_uniform = CameraUniform::new();
         camera_uniform.update_view_proj(&camera);
 
-        let camera_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+        let camera_buffer = ctx.create_buffer_init(&wgpu::util::BufferInitDescriptor {
             label: Some("Camera Uniform Buffer"),
             contents: bytemuck::cast_slice(&[camera_uniform]),
             usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
         });
 
         let camera_bind_group_layout =
-            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            ctx.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
                 label: Some("camera_bind_group_layout"),
                 entries: &[wgpu::BindGroupLayoutEntry {
                     binding: 0,
@@ -316,37 +281,41 @@ impl State {
                 }],
             });
 
-        let camera_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+        let camera_bind_group = ctx.create_bind_group(&wgpu::BindGroupDescriptor {
             label: Some("camera_bind_group"),
-            layout: &camera_bind_group_layout,
+            layout: &camera_bind_group_layout.get(),
             entries: &[wgpu::BindGroupEntry {
                 binding: 0,
-                resource: camera_buffer.as_entire_binding(),
+                resource: camera_buffer.get().as_entire_binding(),
             }],
-        });
+        })?;
 
-        let camera_controller = CameraController::new(0.2);
+        let camera_controller = Box::new(OrbitController::new(12.0));
 
         // Instance
 
-        let instances = (0..NUM_INSTANCES_PER_ROW).flat_map(|z| {
-            (0..NUM_INSTANCES_PER_ROW).map(move |x| {
-                let position = Vector3 {x: x as f32, y: 0.0, z: z as f32} - INSTANCE_DISPLACEMENT;
-
-                let rotation = if position.is_zero() {
-                    Quaternion::from_axis_angle(Vector3::unit_z(), cgmath::Deg(0.0))
-                } else {
-                    Quaternion::from_axis_angle(position.normalize(), cgmath::Deg(45.0))
-                };
-
-                Instance {
-                    position, rotation,
-                }
+        let instances = (0..NUM_INSTANCES_PER_ROW)
+            .flat_map(|z| {
+                (0..NUM_INSTANCES_PER_ROW).map(move |x| {
+                    let position = Vector3 {
+                        x: x as f32,
+                        y: 0.0,
+                        z: z as f32,
+                    } - INSTANCE_DISPLACEMENT;
+
+                    let rotation = if position.is_zero() {
+                        Quaternion::from_axis_angle(Vector3::unit_z(), cgmath::Deg(0.0))
+                    } else {
+                        Quaternion::from_axis_angle(position.normalize(), cgmath::Deg(45.0))
+                    };
+
+                    Instance { position, rotation }
+                })
             })
-        }).collect::<Vec<_>>();
+            .collect::<Vec<_>>();
 
         let instance_data = instances.iter().map(Instance::to_raw).collect::<Vec<_>>();
-        let instance_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+        let instance_buffer = ctx.create_buffer_init(&wgpu::util::BufferInitDescriptor {
             label: Some("Instance Buffer"),
             contents: bytemuck::cast_slice(&instance_data),
             usage: wgpu::BufferUsages::VERTEX,
@@ -354,77 +323,38 @@ impl State {
 
         // Depth
 
-        let depth_texture = texture::Texture::create_depth_texture(&device, &config, "depth_texture");
+        let depth_texture = ctx.create_texture(
+            ctx.size.width,
+            ctx.size.height,
+            &TextureOptions::depth("depth_texture"),
+        );
 
         // Pipeline
 
-        let shader = device.create_shader_module(include_wgsl!("shader.wgsl"));
+        let shader = HotShader::new(ctx, hot_wgsl!("shader.wgsl"))?;
 
         let render_pipeline_layout =
-            device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            ctx.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
                 label: Some("Render Pipeline Layout"),
                 bind_group_layouts: &[
-                    &texture_bind_group_layout,
-                    &camera_bind_group_layout    
+                    &texture_bind_group_layout.get(),
+                    &camera_bind_group_layout.get(),
                 ],
                 push_constant_ranges: &[],
-            });
+            })?;
 
-        let render_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
-            label: Some("Render Pipeline"),
-            layout: Some(&render_pipeline_layout),
-            vertex: wgpu::VertexState {
-                module: &shader,
-                entry_point: "vs_main",
-                buffers: &[
-                    Vertex::desc(),
-                    InstanceRaw::desc(),    
-                ],
-                compilation_options: wgpu::PipelineCompilationOptions::default(),
-            },
-            fragment: Some(wgpu::FragmentState {
-                module: &shader,
-                entry_point: "fs_main",
-                targets: &[Some(wgpu::ColorTargetState {
-                    format: config.format,
-                    blend: Some(wgpu::BlendState::REPLACE),
-                    write_mask: wgpu::ColorWrites::ALL,
-                })],
-                compilation_options: wgpu::PipelineCompilationOptions::default(),
-            }),
-            primitive: wgpu::PrimitiveState {
-                topology: wgpu::PrimitiveTopology::TriangleList,
-                strip_index_format: None,
-                front_face: wgpu::FrontFace::Ccw,
-                cull_mode: Some(wgpu::Face::Back),
-                polygon_mode: wgpu::PolygonMode::Fill,
-                unclipped_depth: false,
-                conservative: false,
-            },
-            depth_stencil: Some(wgpu::DepthStencilState {
-                format: texture::Texture::DEPTH_FORMAT,
-                depth_write_enabled: true,
-                depth_compare: wgpu::CompareFunction::Less,
-                stencil: wgpu::StencilState::default(),
-                bias: wgpu::DepthBiasState::default(),
-            }),
-            multisample: wgpu::MultisampleState {
-                count: 1,
-                mask: !0,
-                alpha_to_coverage_enabled: false,
-            },
-            multiview: None,
-        });
+        let render_pipeline =
+            Self::create_render_pipeline(ctx, &render_pipeline_layout.get(), &shader.module())?;
 
         // Buffers
 
-        let vertex_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+        let vertex_buffer = ctx.create_buffer_init(&wgpu::util::BufferInitDescriptor {
             label: Some("Vertex Buffer"),
             contents: bytemuck::cast_slice(VERTICES),
             usage: wgpu::BufferUsages::VERTEX,
         });
 
-        let index_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+        let index_buffer = ctx.create_buffer_init(&wgpu::util::BufferInitDescriptor {
             label: Some("Index Buffer"),
             contents: bytemuck::cast_slice(INDICES),
             usage: wgpu::BufferUsages::INDEX,
@@ -432,13 +362,10 @@ impl State {
 
         let num_indices = INDICES.len() as u32;
 
-        Self {
-            surface,
-            device,
-            queue,
-            config,
-            size,
+        Ok(Self {
             background_color,
+            shader,
+            render_pipeline_layout,
             render_pipeline,
             vertex_buffer,
             index_buffer,
@@ -446,6 +373,7 @@ impl State {
             diffuse_bind_group,
             diffuse_texture,
             camera,
+            previous_camera: camera,
             camera_uniform,
             camera_buffer,
             camera_bind_group,
@@ -453,177 +381,97 @@ impl State {
             instances,
             instance_buffer,
             depth_texture,
-        }
+        })
     }
 
-    fn resize(&mut self, new_size: winit::dpi::PhysicalSize<u32>) {
-        if new_size.width > 0 && new_size.height > 0 {
-            self.size = new_size;
-            self.config.width = new_size.width;
-            self.config.height = new_size.height;
-            self.surface.configure(&self.device, &self.config);
-            self.depth_texture = texture::Texture::create_depth_texture(&self.device, &self.config, "depth_texture_resize");
-            let _ = self.render();
-        }
+    fn window_attributes() -> WindowAttributes {
+        Window::default_attributes()
+            .with_title("wgpu-template")
+            .with_inner_size(winit::dpi::LogicalSize::new(320.0, 280.0))
     }
 
-    fn update(&mut self) {
-        self.camera_controller.update_camera(&mut self.camera);
-        self.camera_uniform.update_view_proj(&self.camera);
-        self.queue.write_buffer(&self.camera_buffer, 0, bytemuck::cast_slice(&[self.camera_uniform]));
+    fn redraw_policy() -> RedrawPolicy {
+        RedrawPolicy::Continuous
     }
 
-    fn render(&mut self) -> Result<(), wgpu::SurfaceError> {
-        let output = self.surface.get_current_texture()?;
+    fn resize(&mut self, ctx: &GpuContext) {
+        self.camera
+            .projection
+            .resize(ctx.size.width, ctx.size.height);
+        self.depth_texture = ctx.create_texture(
+            ctx.size.width,
+            ctx.size.height,
+            &TextureOptions::depth("depth_texture_resize"),
+        );
+    }
 
-        let view = output
-            .texture
-            .create_view(&wgpu::TextureViewDescriptor::default());
+    fn reload_shaders(&mut self, ctx: &GpuContext) {
+        let layout = self.render_pipeline_layout.get();
+        let rebuilt = self.shader.reload_with(ctx, |shader| {
+            Self::create_render_pipeline(ctx, &layout, shader)
+        });
+        if let Some(render_pipeline) = rebuilt {
+            self.render_pipeline = render_pipeline;
+        }
+    }
 
-        // The `encoder` builds a command buffer that we can then send to the GPU.
-        let mut encoder = self
-            .device
-            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
-                label: Some("Render Encoder"),
-            });
+    fn update(&mut self, ctx: &GpuContext, dt: Duration) {
+        self.previous_camera = self.camera;
+        self.camera_controller
+            .update_camera(&mut self.camera, &ctx.input, dt);
+    }
 
-        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
-            label: Some("Render Pass"),
-            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                view: &view,
-                resolve_target: None,
-                ops: wgpu::Operations {
-                    load: wgpu::LoadOp::Clear(self.background_color),
-                    store: wgpu::StoreOp::Store,
-                },
-            })],
-            depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
-                view: &self.depth_texture.view,
-                depth_ops: Some(wgpu::Operations {
-                    load: wgpu::LoadOp::Clear(1.0),
-                    store: wgpu::StoreOp::Store,
+    fn render(&mut self, frame: &mut Frame) {
+        let camera = self.previous_camera.interpolate(&self.camera, frame.alpha);
+        self.camera_uniform.update_view_proj(&camera);
+        frame.queue.write_buffer(
+            &self.camera_buffer.get(),
+            0,
+            bytemuck::cast_slice(&[self.camera_uniform]),
+        );
+
+        let render_pipeline = self.render_pipeline.get();
+        let diffuse_bind_group = self.diffuse_bind_group.get();
+        let camera_bind_group = self.camera_bind_group.get();
+        let vertex_buffer = self.vertex_buffer.get();
+        let instance_buffer = self.instance_buffer.get();
+        let depth_texture = self.depth_texture.get();
+        let index_buffer = self.index_buffer.get();
+
+        let mut render_pass = frame
+            .encoder
+            .begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Render Pass"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: frame.view,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(self.background_color),
+                        store: wgpu::StoreOp::Store,
+                    },
+                })],
+                depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                    view: &depth_texture.view,
+                    depth_ops: Some(wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(1.0),
+                        store: wgpu::StoreOp::Store,
+                    }),
+                    stencil_ops: None,
                 }),
-                stencil_ops: None,
-            }),
-            occlusion_query_set: None,
-            timestamp_writes: None,
-        });
+                occlusion_query_set: None,
+                timestamp_writes: frame.profiler.render_pass_timestamps("Render Pass"),
+            });
 
-        render_pass.set_pipeline(&self.render_pipeline);
-        render_pass.set_bind_group(0, &self.diffuse_bind_group, &[]);
-        render_pass.set_bind_group(1, &self.camera_bind_group, &[]);
-        render_pass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
-        render_pass.set_vertex_buffer(1, self.instance_buffer.slice(..));
-        render_pass.set_index_buffer(self.index_buffer.slice(..), wgpu::IndexFormat::Uint16);
+        render_pass.set_pipeline(&render_pipeline);
+        render_pass.set_bind_group(0, &diffuse_bind_group, &[]);
+        render_pass.set_bind_group(1, &camera_bind_group, &[]);
+        render_pass.set_vertex_buffer(0, vertex_buffer.slice(..));
+        render_pass.set_vertex_buffer(1, instance_buffer.slice(..));
+        render_pass.set_index_buffer(index_buffer.slice(..), wgpu::IndexFormat::Uint16);
 
         render_pass.draw_indexed(0..self.num_indices, 0, 0..self.instances.len() as _);
 
         // begin_render_pass() borrows encoder mutably (aka &mut self). We can't call encoder.finish() until we release that mutable borrow.
         drop(render_pass);
-
-        self.queue.submit(std::iter::once(encoder.finish()));
-        output.present();
-
-        Ok(())
     }
 }
-
-impl ApplicationHandler for App {
-    fn resumed(&mut self, event_loop: &ActiveEventLoop) {
-        println!("App Resumed !");
-
-        // Initialized at first Resumed Event
-        if self.window.is_none() {
-            println!("Create Window !");
-            let window_attributes = Window::default_attributes()
-                .with_title("wgpu-template")
-                .with_inner_size(winit::dpi::LogicalSize::new(320.0, 280.0));
-
-            let window = Arc::new(event_loop.create_window(window_attributes).unwrap());
-            let state = pollster::block_on(State::new(Arc::clone(&window)));
-            println!("Bind Window !");
-
-            self.scale_factor = window.scale_factor();
-            self.window = Some(window);
-            self.state = Some(state);
-            self.window.as_ref().unwrap().pre_present_notify();
-            self.window.as_ref().unwrap().request_redraw();
-        }
-    }
-
-    fn suspended(&mut self, event_loop: &ActiveEventLoop) {
-        println!("App Suspended !");
-    }
-
-    fn new_events(&mut self, event_loop: &ActiveEventLoop, cause: winit::event::StartCause) {
-        // println!("new_events: {cause:?}");
-    }
-
-    fn window_event(
-        &mut self,
-        event_loop: &ActiveEventLoop,
-        _window_id: WindowId,
-        event: WindowEvent,
-    ) {
-        if self.input(&event) {
-            return;
-        }
-        // println!("WindowEvent: {event:?}");
-        match event {
-            WindowEvent::CloseRequested => self.exit(event_loop),
-            WindowEvent::KeyboardInput {
-                event:
-                    KeyEvent {
-                        physical_key,
-                        state: ElementState::Pressed,
-                        ..
-                    },
-                ..
-            } => match physical_key {
-                PhysicalKey::Code(KeyCode::Escape) => self.exit(event_loop),
-                _ => (),
-            },
-            WindowEvent::RedrawRequested => {
-                let state = self.state.as_mut().unwrap();
-                state.update();
-                match state.render() {
-                    Ok(_) => {}
-                    Err(e) => {
-                        eprintln!("{:?}", e);
-                        match e {
-                            wgpu::SurfaceError::Lost => state.resize(state.size),
-                            wgpu::SurfaceError::OutOfMemory => self.exit(event_loop),
-                            _ => (),
-                        }
-                    }
-                }
-                self.window.as_mut().unwrap().request_redraw();
-            }
-            WindowEvent::Resized(physical_size) => {
-                println!("On Resized !");
-                self.state.as_mut().unwrap().resize(physical_size);
-            }
-            WindowEvent::ScaleFactorChanged {
-                scale_factor,
-                mut inner_size_writer,
-            } => {
-                let PhysicalSize { width, height } = self.state.as_ref().unwrap().size;
-                let new_width = width as f64 / self.scale_factor * scale_factor;
-                let new_height = height as f64 / self.scale_factor * scale_factor;
-                let new_inner_size =
-                    PhysicalSize::new(new_width.floor() as u32, new_height.floor() as u32);
-                let _ = inner_size_writer.request_inner_size(new_inner_size);
-                println!("Request new size: {new_inner_size:?}");
-            }
-            _ => (),
-        }
-    }
-}
-
-pub fn run() -> Result<(), winit::error::EventLoopError> {
-    let mut app = App::default();
-    let event_loop = EventLoop::new().unwrap();
-
-    event_loop.set_control_flow(ControlFlow::Poll);
-    event_loop.run_app(&mut app)
-}
@@ -1,6 +1,8 @@
 use std::ops::Range;
+use std::sync::Arc;
 
-use crate::texture;
+use wgpu_template::texture;
+use wgpu_template::{InstrumentedRenderPass, Restorable, TrackedBuffer};
 
 pub trait Vertex {
     fn desc() -> wgpu::VertexBufferLayout<'static>;
@@ -45,77 +47,120 @@ pub struct Model {
     pub materials: Vec<Material>,
 }
 
+impl Model {
+    /// The model's buffers and bind groups on the current device, to draw it with.
+    pub fn objects(&self) -> ModelObjects {
+        ModelObjects {
+            meshes: self.meshes.iter().map(Mesh::objects).collect(),
+            materials: self
+                .materials
+                .iter()
+                .map(|material| material.bind_group.get())
+                .collect(),
+        }
+    }
+}
+
+#[allow(dead_code)]
 pub struct Material {
     pub name: String,
-    pub diffuse_texture: texture::Texture,
-    pub bind_group: wgpu::BindGroup,
+    pub diffuse_texture: Restorable<texture::Texture>,
+    pub bind_group: Restorable<wgpu::BindGroup>,
 }
 
+#[allow(dead_code)]
 pub struct Mesh {
     pub name: String,
-    pub vertex_buffer: wgpu::Buffer,
-    pub index_buffer: wgpu::Buffer,
+    pub vertex_buffer: Restorable<TrackedBuffer>,
+    pub index_buffer: Restorable<TrackedBuffer>,
+    pub num_elements: u32,
+    pub material: usize,
+}
+
+impl Mesh {
+    pub fn objects(&self) -> MeshObjects {
+        MeshObjects {
+            vertex_buffer: self.vertex_buffer.get(),
+            index_buffer: self.index_buffer.get(),
+            num_elements: self.num_elements,
+            material: self.material,
+        }
+    }
+}
+
+/// What a render pass draws a [`Model`] with. The pass borrows it, so it is taken before the
+/// pass begins.
+pub struct ModelObjects {
+    pub meshes: Vec<MeshObjects>,
+    /// The bind groups of the materials.
+    pub materials: Vec<Arc<wgpu::BindGroup>>,
+}
+
+pub struct MeshObjects {
+    pub vertex_buffer: Arc<TrackedBuffer>,
+    pub index_buffer: Arc<TrackedBuffer>,
     pub num_elements: u32,
     pub material: usize,
 }
 
+#[allow(dead_code)]
 pub trait DrawModel<'a> {
     fn draw_mesh(
         &mut self,
-        mesh: &'a Mesh,
-        material: &'a Material,
+        mesh: &'a MeshObjects,
+        material: &'a wgpu::BindGroup,
         camera_bind_group: &'a wgpu::BindGroup,
     );
     fn draw_mesh_instanced(
         &mut self,
-        mesh: &'a Mesh,
-        material: &'a Material,
+        mesh: &'a MeshObjects,
+        material: &'a wgpu::BindGroup,
         instances: Range<u32>,
         camera_bind_group: &'a wgpu::BindGroup,
     );
-    fn draw_model(&mut self, model: &'a Model, camera_bind_group: &'a wgpu::BindGroup);
+    fn draw_model(&mut self, model: &'a ModelObjects, camera_bind_group: &'a wgpu::BindGroup);
     fn draw_model_instanced(
         &mut self,
-        model: &'a Model,
+        model: &'a ModelObjects,
         instances: Range<u32>,
         camera_bind_group: &'a wgpu::BindGroup,
     );
 }
 
-impl<'a, 'b> DrawModel<'b> for wgpu::RenderPass<'a>
+impl<'a, 'b> DrawModel<'b> for InstrumentedRenderPass<'a>
 where
     'b: 'a,
 {
     fn draw_mesh(
         &mut self,
-        mesh: &'b Mesh,
-        material: &'a Material,
+        mesh: &'b MeshObjects,
+        material: &'a wgpu::BindGroup,
         camera_bind_group: &'a wgpu::BindGroup,
     ) {
-        self.draw_mesh_instanced(mesh, &material, 0..1, camera_bind_group);
+        self.draw_mesh_instanced(mesh, material, 0..1, camera_bind_group);
     }
 
     fn draw_mesh_instanced(
         &mut self,
-        mesh: &'a Mesh,
-        material: &'a Material,
+        mesh: &'a MeshObjects,
+        material: &'a wgpu::BindGroup,
         instances: Range<u32>,
         camera_bind_group: &'a wgpu::BindGroup,
     ) {
         self.set_vertex_buffer(0, mesh.vertex_buffer.slice(..));
         self.set_index_buffer(mesh.index_buffer.slice(..), wgpu::IndexFormat::Uint32);
-        self.set_bind_group(0, &material.bind_group, &[]);
+        self.set_bind_group(0, material, &[]);
         self.set_bind_group(1, camera_bind_group, &[]);
         self.draw_indexed(0..mesh.num_elements, 0, instances);
     }
 
-    fn draw_model(&mut self, model: &'b Model, camera_bind_group: &'b wgpu::BindGroup) {
+    fn draw_model(&mut self, model: &'b ModelObjects, camera_bind_group: &'b wgpu::BindGroup) {
         self.draw_model_instanced(model, 0..1, camera_bind_group);
     }
 
     fn draw_model_instanced(
         &mut self,
-        model: &'b Model,
+        model: &'b ModelObjects,
         instances: Range<u32>,
         camera_bind_group: &'b wgpu::BindGroup,
     ) {
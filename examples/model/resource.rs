@@ -1,36 +1,35 @@
-use crate::{model, texture};
+use super::model;
 use std::io::{BufReader, Cursor};
-use wgpu::util::DeviceExt;
+use wgpu_template::{texture, Error, GpuContext, Restorable};
 
-pub async fn load_string(file_name: &str) -> anyhow::Result<String> {
+pub async fn load_string(file_name: &str) -> wgpu_template::Result<String> {
     let path = std::path::Path::new("./examples/res").join(file_name);
     println!("string path: {path:?}");
-    let txt = std::fs::read_to_string(path)?;
+    let txt =
+        std::fs::read_to_string(&path).map_err(|e| Error::asset(path.display().to_string(), e))?;
     Ok(txt)
 }
 
-pub async fn load_binary(file_name: &str) -> anyhow::Result<Vec<u8>> {
+pub async fn load_binary(file_name: &str) -> wgpu_template::Result<Vec<u8>> {
     let path = std::path::Path::new("./examples/res").join(file_name);
     println!("binary path: {path:?}");
-    let bin = std::fs::read(path)?;
+    let bin = std::fs::read(&path).map_err(|e| Error::asset(path.display().to_string(), e))?;
     Ok(bin)
 }
 
 pub async fn load_texture(
     file_name: &str,
-    device: &wgpu::Device,
-    queue: &wgpu::Queue,
-) -> anyhow::Result<texture::Texture> {
+    ctx: &GpuContext,
+) -> wgpu_template::Result<Restorable<texture::Texture>> {
     let data = load_binary(file_name).await?;
-    texture::Texture::from_bytes(device, queue, &data, file_name)
+    ctx.create_texture_from_bytes(&data, file_name)
 }
 
 pub async fn load_model(
     file_name: &str,
-    device: &wgpu::Device,
-    queue: &wgpu::Queue,
+    ctx: &GpuContext,
     layout: &wgpu::BindGroupLayout,
-) -> anyhow::Result<model::Model> {
+) -> wgpu_template::Result<model::Model> {
     let obj_text = load_string(file_name).await?;
     let obj_cursor = Cursor::new(obj_text);
     let mut obj_reader = BufReader::new(obj_cursor);
@@ -43,29 +42,32 @@ pub async fn load_model(
             ..Default::default()
         },
         |p| async move {
-            let mat_text = load_string(&p).await.unwrap();
+            let Ok(mat_text) = load_string(&p).await else {
+                return Err(tobj::LoadError::OpenFileFailed);
+            };
             tobj::load_mtl_buf(&mut BufReader::new(Cursor::new(mat_text)))
         },
     )
-    .await?;
+    .await
+    .map_err(|e| Error::asset(file_name, e))?;
 
     let mut materials = Vec::new();
-    for m in obj_materials? {
-        let diffuse_texture = load_texture(&m.diffuse_texture, device, queue).await?;
-        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+    for m in obj_materials.map_err(|e| Error::asset(file_name, e))? {
+        let diffuse_texture = load_texture(&m.diffuse_texture, ctx).await?;
+        let bind_group = ctx.create_bind_group(&wgpu::BindGroupDescriptor {
             layout,
             entries: &[
                 wgpu::BindGroupEntry {
                     binding: 0,
-                    resource: wgpu::BindingResource::TextureView(&diffuse_texture.view),
+                    resource: wgpu::BindingResource::TextureView(&diffuse_texture.get().view),
                 },
                 wgpu::BindGroupEntry {
                     binding: 1,
-                    resource: wgpu::BindingResource::Sampler(&diffuse_texture.sampler),
+                    resource: wgpu::BindingResource::Sampler(&diffuse_texture.get().sampler),
                 },
             ],
             label: Some(&format!("Material: {:?} {:?}", &m.name, file_name)),
-        });
+        })?;
 
         materials.push(model::Material {
             name: m.name,
@@ -73,29 +75,29 @@ pub async fn load_model(
             bind_group,
         })
     }
-    
-    if materials.len() == 0 {
-        let diffuse_texture = load_texture("smile.png", device, queue).await?;
-        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+
+    if materials.is_empty() {
+        let diffuse_texture = load_texture("smile.png", ctx).await?;
+        let bind_group = ctx.create_bind_group(&wgpu::BindGroupDescriptor {
             layout,
             entries: &[
                 wgpu::BindGroupEntry {
                     binding: 0,
-                    resource: wgpu::BindingResource::TextureView(&diffuse_texture.view),
+                    resource: wgpu::BindingResource::TextureView(&diffuse_texture.get().view),
                 },
                 wgpu::BindGroupEntry {
                     binding: 1,
-                    resource: wgpu::BindingResource::Sampler(&diffuse_texture.sampler),
+                    resource: wgpu::BindingResource::Sampler(&diffuse_texture.get().sampler),
                 },
             ],
             label: None,
-        });
+        })?;
 
         materials.push(model::Material {
             name: "fallback".into(),
             diffuse_texture,
             bind_group,
-        })   
+        })
     }
 
     let meshes = models
@@ -113,16 +115,16 @@ pub async fn load_model(
                         m.mesh.normals[i * 3],
                         m.mesh.normals[i * 3 + 1],
                         m.mesh.normals[i * 3 + 2],
-                    ]
+                    ],
                 })
                 .collect::<Vec<_>>();
 
-            let vertex_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            let vertex_buffer = ctx.create_buffer_init(&wgpu::util::BufferInitDescriptor {
                 label: Some(&format!("{:?} Vertex Buffer", file_name)),
                 contents: bytemuck::cast_slice(&vertices),
                 usage: wgpu::BufferUsages::VERTEX,
             });
-            let index_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            let index_buffer = ctx.create_buffer_init(&wgpu::util::BufferInitDescriptor {
                 label: Some(&format!("{:?} Index Buffer", file_name)),
                 contents: bytemuck::cast_slice(&m.mesh.indices),
                 usage: wgpu::BufferUsages::INDEX,
@@ -138,5 +140,5 @@ pub async fn load_model(
         })
         .collect::<Vec<_>>();
 
-    Ok(model::Model {meshes, materials})
-}
\ No newline at end of file
+    Ok(model::Model { meshes, materials })
+}
@@ -1,216 +1,57 @@
-use std::sync::Arc;
-use winit::application::ApplicationHandler;
-use winit::dpi::PhysicalSize;
-use winit::event::{ElementState, KeyEvent, WindowEvent};
-use winit::event_loop::{ActiveEventLoop, ControlFlow, EventLoop};
-use winit::keyboard::{KeyCode, PhysicalKey};
-use winit::window::{Window, WindowId};
+use wgpu_template::{
+    hot_wgsl, Application, Binding, Bindings, Frame, GpuContext, HotShader, RendererConfig,
+    Restorable,
+};
+use winit::event::WindowEvent;
+use winit::keyboard::KeyCode;
+use winit::window::{Window, WindowAttributes};
 
 pub fn main() -> Result<(), impl std::error::Error> {
-    run()
+    wgpu_template::run::<Example>()
 }
 
-#[derive(Default)]
-struct App {
-    window: Option<Arc<Window>>,
-    state: Option<State>,
-    scale_factor: f64,
-}
-
-impl App {
-    fn input(&mut self, event: &WindowEvent) -> bool {
-        match event {
-            WindowEvent::CursorMoved { position, .. } => {
-                let state = self.state.as_mut().unwrap();
-                state.background_color.r = position.x / state.size.width as f64;
-                state.background_color.g = position.y / state.size.height as f64;
-                state.background_color.b =
-                    (position.x + position.y) / (state.size.height + state.size.height) as f64;
-                self.window.as_ref().unwrap().request_redraw();
-                true
-            }
-            WindowEvent::KeyboardInput {
-                event:
-                    KeyEvent {
-                        physical_key: PhysicalKey::Code(KeyCode::Space),
-                        state: ElementState::Pressed,
-                        ..
-                    },
-                ..
-            } => {
-                let state = self.state.as_mut().unwrap();
-                let device = &state.device;
-                let config = &state.config;
-
-                let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
-                    label: Some("Shader When Press Space"),
-                    source: wgpu::ShaderSource::Wgsl(include_str!("shader_press_space.wgsl").into()),
-                });
-
-                let render_pipeline_layout =
-                    device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
-                        label: Some("Render Pipeline Layout"),
-                        bind_group_layouts: &[],
-                        push_constant_ranges: &[],
-                    });
+/// Switches to the other fragment shader.
+const TOGGLE_SHADER: &str = "toggle_shader";
+/// Draws the triangle's edges only, where the adapter supports it.
+const TOGGLE_WIREFRAME: &str = "toggle_wireframe";
 
-                state.render_pipeline =
-                    device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
-                        label: Some("Render Pipeline"),
-                        layout: Some(&render_pipeline_layout),
-                        vertex: wgpu::VertexState {
-                            module: &shader,
-                            entry_point: "vs_main",
-                            buffers: &[],
-                            compilation_options: wgpu::PipelineCompilationOptions::default(),
-                        },
-                        fragment: Some(wgpu::FragmentState {
-                            module: &shader,
-                            entry_point: "fs_main",
-                            targets: &[Some(wgpu::ColorTargetState {
-                                format: config.format,
-                                blend: Some(wgpu::BlendState::REPLACE),
-                                write_mask: wgpu::ColorWrites::ALL,
-                            })],
-                            compilation_options: wgpu::PipelineCompilationOptions::default(),
-                        }),
-                        primitive: wgpu::PrimitiveState {
-                            topology: wgpu::PrimitiveTopology::TriangleList,
-                            strip_index_format: None,
-                            front_face: wgpu::FrontFace::Ccw,
-                            cull_mode: Some(wgpu::Face::Back),
-                            polygon_mode: wgpu::PolygonMode::Fill,
-                            unclipped_depth: false,
-                            conservative: false,
-                        },
-                        depth_stencil: None,
-                        multisample: wgpu::MultisampleState {
-                            count: 1,
-                            mask: !0,
-                            alpha_to_coverage_enabled: false,
-                        },
-                        multiview: None,
-                    });
-                
-                self.window.as_ref().unwrap().request_redraw();
-                true
-            }
-            _ => false,
-        }
-    }
-
-    fn exit(&mut self, event_loop: &ActiveEventLoop) {
-        println!("Exit App !");
-
-        // https://github.com/rust-windowing/winit/issues/3668
-        // Use Option::take to Dispose Option<Arc<Window>>
-        self.window.take();
-        self.state.take();
-        event_loop.exit();
-    }
-}
-
-struct State {
-    surface: wgpu::Surface<'static>,
-    device: wgpu::Device,
-    queue: wgpu::Queue,
-    config: wgpu::SurfaceConfiguration,
-    size: winit::dpi::PhysicalSize<u32>,
+pub struct Example {
     background_color: wgpu::Color,
-    render_pipeline: wgpu::RenderPipeline,
+    /// `shader.wgsl` and `shader_press_space.wgsl`, reloaded when saved with `--hot-reload`.
+    shaders: [HotShader; 2],
+    /// The shader drawn with.
+    current: usize,
+    render_pipeline: Restorable<wgpu::RenderPipeline>,
+    wireframe: bool,
 }
 
-impl State {
-    async fn new(window: Arc<Window>) -> Self {
-        let size = window.inner_size();
-
-        let instance = wgpu::Instance::new(wgpu::InstanceDescriptor {
-            backends: wgpu::Backends::PRIMARY,
-            ..Default::default()
-        });
-
-        let surface = instance.create_surface(window).unwrap();
-
-        let adapter = instance
-            .request_adapter(&wgpu::RequestAdapterOptions {
-                power_preference: wgpu::PowerPreference::default(),
-                compatible_surface: Some(&surface),
-                force_fallback_adapter: false,
-            })
-            .await
-            .unwrap();
-
-        let (device, queue) = adapter
-            .request_device(
-                &wgpu::DeviceDescriptor {
-                    required_features: wgpu::Features::empty(),
-                    required_limits: wgpu::Limits::default(),
-                    label: None,
-                },
-                None,
-            )
-            .await
-            .unwrap();
-
-        let surface_caps = surface.get_capabilities(&adapter);
-
-        let surface_format = surface_caps
-            .formats
-            .iter()
-            .copied()
-            .filter(|f| f.is_srgb())
-            .next()
-            .unwrap_or(surface_caps.formats[0]);
-
-        let modes = &surface_caps.present_modes;
-        println!("Available present_modes: {modes:?}");
-        println!("Available surface_formats: {:?}", &surface_caps.formats);
-        println!("Current surface_format: {:?}", surface_format);
-
-        let config = wgpu::SurfaceConfiguration {
-            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
-            format: surface_format,
-            width: size.width,
-            height: size.height,
-            present_mode: surface_caps.present_modes[0],
-            alpha_mode: surface_caps.alpha_modes[0],
-            view_formats: vec![],
-            desired_maximum_frame_latency: 2,
-        };
-
-        let background_color = wgpu::Color {
-            r: 0.0,
-            g: 0.0,
-            b: 0.0,
-            a: 1.0,
-        };
-
-        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
-            label: Some("Shader"),
-            source: wgpu::ShaderSource::Wgsl(include_str!("shader.wgsl").into()),
-        });
-
+impl Example {
+    fn create_render_pipeline(
+        ctx: &GpuContext,
+        shader: &wgpu::ShaderModule,
+        polygon_mode: wgpu::PolygonMode,
+    ) -> wgpu_template::Result<Restorable<wgpu::RenderPipeline>> {
         let render_pipeline_layout =
-            device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            ctx.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
                 label: Some("Render Pipeline Layout"),
                 bind_group_layouts: &[],
                 push_constant_ranges: &[],
-            });
+            })?;
 
-        let render_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+        ctx.try_create_render_pipeline(&wgpu::RenderPipelineDescriptor {
             label: Some("Render Pipeline"),
-            layout: Some(&render_pipeline_layout),
+            layout: Some(&render_pipeline_layout.get()),
             vertex: wgpu::VertexState {
-                module: &shader,
+                module: shader,
                 entry_point: "vs_main",
                 buffers: &[],
                 compilation_options: wgpu::PipelineCompilationOptions::default(),
             },
             fragment: Some(wgpu::FragmentState {
-                module: &shader,
+                module: shader,
                 entry_point: "fs_main",
                 targets: &[Some(wgpu::ColorTargetState {
-                    format: config.format,
+                    format: ctx.format,
                     blend: Some(wgpu::BlendState::REPLACE),
                     write_mask: wgpu::ColorWrites::ALL,
                 })],
@@ -221,7 +62,7 @@ impl State {
                 strip_index_format: None,
                 front_face: wgpu::FrontFace::Ccw,
                 cull_mode: Some(wgpu::Face::Back),
-                polygon_mode: wgpu::PolygonMode::Fill,
+                polygon_mode,
                 unclipped_depth: false,
                 conservative: false,
             },
@@ -232,170 +73,141 @@ impl State {
                 alpha_to_coverage_enabled: false,
             },
             multiview: None,
-        });
-
-        Self {
-            surface,
-            device,
-            queue,
-            config,
-            size,
-            background_color,
-            render_pipeline,
-        }
+        })
     }
 
-    fn resize(&mut self, new_size: winit::dpi::PhysicalSize<u32>) {
-        if new_size.width > 0 && new_size.height > 0 {
-            self.size = new_size;
-            self.config.width = new_size.width;
-            self.config.height = new_size.height;
-            self.surface.configure(&self.device, &self.config);
-            let _ = self.render();
+    /// Rebuilds the pipeline for the current shader and polygon mode. If the shader no longer
+    /// fits the pipeline, the error is reported and the last good pipeline kept.
+    fn rebuild_pipeline(&mut self, ctx: &GpuContext) {
+        let shader = self.shaders[self.current].module();
+        match Self::create_render_pipeline(ctx, &shader, self.polygon_mode()) {
+            Ok(render_pipeline) => self.render_pipeline = render_pipeline,
+            Err(e) => log::error!("{e}, keeping the previous pipeline"),
         }
     }
 
-    fn update(&mut self) {
-        // todo!()
+    fn polygon_mode(&self) -> wgpu::PolygonMode {
+        if self.wireframe {
+            wgpu::PolygonMode::Line
+        } else {
+            wgpu::PolygonMode::Fill
+        }
     }
+}
 
-    fn render(&mut self) -> Result<(), wgpu::SurfaceError> {
-        let output = self.surface.get_current_texture()?;
-
-        let view = output
-            .texture
-            .create_view(&wgpu::TextureViewDescriptor::default());
-
-        // The `encoder` builds a command buffer that we can then send to the GPU.
-        let mut encoder = self
-            .device
-            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
-                label: Some("Render Encoder"),
-            });
-
-        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
-            label: Some("Render Pass"),
-            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                view: &view,
-                resolve_target: None,
-                ops: wgpu::Operations {
-                    load: wgpu::LoadOp::Clear(self.background_color),
-                    store: wgpu::StoreOp::Store,
-                },
-            })],
-            depth_stencil_attachment: None,
-            occlusion_query_set: None,
-            timestamp_writes: None,
-        });
+impl Application for Example {
+    fn init(ctx: &GpuContext) -> wgpu_template::Result<Self> {
+        let background_color = wgpu::Color {
+            r: 0.0,
+            g: 0.0,
+            b: 0.0,
+            a: 1.0,
+        };
 
-        render_pass.set_pipeline(&self.render_pipeline);
-        render_pass.draw(0..3, 0..1);
+        let shaders = [
+            HotShader::new(ctx, hot_wgsl!("shader.wgsl"))?,
+            HotShader::new(ctx, hot_wgsl!("shader_press_space.wgsl"))?,
+        ];
 
-        // begin_render_pass() borrows encoder mutably (aka &mut self). We can't call encoder.finish() until we release that mutable borrow.
-        drop(render_pass);
+        let render_pipeline =
+            Self::create_render_pipeline(ctx, &shaders[0].module(), wgpu::PolygonMode::Fill)?;
 
-        self.queue.submit(std::iter::once(encoder.finish()));
-        output.present();
-
-        Ok(())
+        Ok(Self {
+            background_color,
+            shaders,
+            current: 0,
+            render_pipeline,
+            wireframe: false,
+        })
     }
-}
-
-impl ApplicationHandler for App {
-    fn resumed(&mut self, event_loop: &ActiveEventLoop) {
-        println!("App Resumed !");
-
-        // Initialized at first Resumed Event
-        if self.window.is_none() {
-            println!("Create Window !");
-            let window_attributes = Window::default_attributes()
-                .with_title("wgpu-template")
-                .with_inner_size(winit::dpi::LogicalSize::new(320.0, 280.0));
 
-            let window = Arc::new(event_loop.create_window(window_attributes).unwrap());
-            let state = pollster::block_on(State::new(Arc::clone(&window)));
-            println!("Bind Window !");
-
-            self.scale_factor = window.scale_factor();
-            self.window = Some(window);
-            self.state = Some(state);
-            self.window.as_ref().unwrap().pre_present_notify();
-            self.window.as_ref().unwrap().request_redraw();
-        }
+    fn window_attributes() -> WindowAttributes {
+        Window::default_attributes()
+            .with_title("wgpu-template")
+            .with_inner_size(winit::dpi::LogicalSize::new(320.0, 280.0))
     }
 
-    fn suspended(&mut self, event_loop: &ActiveEventLoop) {
-        println!("App Suspended !");
+    fn renderer_config() -> RendererConfig {
+        // Wireframe rendering is a nice-to-have: the example still runs without it.
+        RendererConfig::default().optional_features(wgpu::Features::POLYGON_MODE_LINE)
     }
 
-    fn new_events(&mut self, event_loop: &ActiveEventLoop, cause: winit::event::StartCause) {
-        // println!("new_events: {cause:?}");
+    fn bindings() -> Bindings {
+        Bindings::default()
+            .bind(TOGGLE_SHADER, [Binding::Key(KeyCode::Space)])
+            .bind(TOGGLE_WIREFRAME, [Binding::Key(KeyCode::KeyL)])
     }
 
-    fn window_event(
-        &mut self,
-        event_loop: &ActiveEventLoop,
-        _window_id: WindowId,
-        event: WindowEvent,
-    ) {
-        if self.input(&event) {
-            return;
-        }
-        // println!("WindowEvent: {event:?}");
+    fn input(&mut self, ctx: &GpuContext, event: &WindowEvent) -> bool {
         match event {
-            WindowEvent::CloseRequested => self.exit(event_loop),
-            WindowEvent::KeyboardInput {
-                event:
-                    KeyEvent {
-                        physical_key,
-                        state: ElementState::Pressed,
-                        ..
-                    },
-                ..
-            } => match physical_key {
-                PhysicalKey::Code(KeyCode::Escape) => self.exit(event_loop),
-                _ => (),
-            },
-            WindowEvent::RedrawRequested => {
-                let state = self.state.as_mut().unwrap();
-                state.update();
-                match state.render() {
-                    Ok(_) => {}
-                    Err(e) => {
-                        eprintln!("{:?}", e);
-                        match e {
-                            wgpu::SurfaceError::Lost => state.resize(state.size),
-                            wgpu::SurfaceError::OutOfMemory => self.exit(event_loop),
-                            _ => (),
-                        }
-                    }
+            WindowEvent::CursorMoved { position, .. } => {
+                let size = ctx.size;
+                self.background_color.r = position.x / size.width as f64;
+                self.background_color.g = position.y / size.height as f64;
+                self.background_color.b =
+                    (position.x + position.y) / (size.height + size.height) as f64;
+                true
+            }
+            _ if ctx.input.bindings().is_triggered(TOGGLE_SHADER, event) => {
+                self.current = 1 - self.current;
+                self.rebuild_pipeline(ctx);
+
+                true
+            }
+            _ if ctx.input.bindings().is_triggered(TOGGLE_WIREFRAME, event) => {
+                if !ctx.has_features(wgpu::Features::POLYGON_MODE_LINE) {
+                    println!("Wireframe is not supported by this adapter");
+                    return false;
                 }
+                self.wireframe = !self.wireframe;
+                self.rebuild_pipeline(ctx);
+
+                true
             }
-            WindowEvent::Resized(physical_size) => {
-                println!("On Resized !");
-                self.state.as_mut().unwrap().resize(physical_size);
+            _ => false,
+        }
+    }
+
+    fn reload_shaders(&mut self, ctx: &GpuContext) {
+        let polygon_mode = self.polygon_mode();
+        for (index, shader) in self.shaders.iter_mut().enumerate() {
+            if index != self.current {
+                shader.reload(ctx);
+                continue;
             }
-            WindowEvent::ScaleFactorChanged {
-                scale_factor,
-                mut inner_size_writer,
-            } => {
-                let PhysicalSize { width, height } = self.state.as_ref().unwrap().size;
-                let new_width = width as f64 / self.scale_factor * scale_factor;
-                let new_height = height as f64 / self.scale_factor * scale_factor;
-                let new_inner_size =
-                    PhysicalSize::new(new_width.floor() as u32, new_height.floor() as u32);
-                let _ = inner_size_writer.request_inner_size(new_inner_size);
-                println!("Request new size: {new_inner_size:?}");
+            let rebuilt = shader.reload_with(ctx, |shader| {
+                Self::create_render_pipeline(ctx, shader, polygon_mode)
+            });
+            if let Some(render_pipeline) = rebuilt {
+                self.render_pipeline = render_pipeline;
             }
-            _ => (),
         }
     }
-}
 
-pub fn run() -> Result<(), winit::error::EventLoopError> {
-    let mut app = App::default();
-    let event_loop = EventLoop::new().unwrap();
+    fn render(&mut self, frame: &mut Frame) {
+        let render_pipeline = self.render_pipeline.get();
+
+        let mut render_pass = frame
+            .encoder
+            .begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Render Pass"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: frame.view,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(self.background_color),
+                        store: wgpu::StoreOp::Store,
+                    },
+                })],
+                depth_stencil_attachment: None,
+                occlusion_query_set: None,
+                timestamp_writes: frame.profiler.render_pass_timestamps("Render Pass"),
+            });
 
-    event_loop.set_control_flow(ControlFlow::Poll);
-    event_loop.run_app(&mut app)
+        render_pass.set_pipeline(&render_pipeline);
+        render_pass.draw(0..3, 0..1);
+
+        // begin_render_pass() borrows encoder mutably (aka &mut self). We can't call encoder.finish() until we release that mutable borrow.
+        drop(render_pass);
+    }
 }
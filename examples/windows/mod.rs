@@ -0,0 +1,340 @@
+use std::collections::HashMap;
+use std::time::Duration;
+use wgpu_template::{
+    hot_wgsl, texture, Application, Binding, Bindings, Frame, GpuContext, HotShader, RedrawPolicy,
+    Restorable, TrackedBuffer,
+};
+use winit::event::WindowEvent;
+use winit::keyboard::KeyCode;
+use winit::window::{Window, WindowAttributes, WindowId};
+
+pub fn main() -> Result<(), impl std::error::Error> {
+    wgpu_template::run::<Example>()
+}
+
+/// Opens another window.
+const OPEN_WINDOW: &str = "open_window";
+/// Closes the focused window.
+const CLOSE_WINDOW: &str = "close_window";
+
+/// Background and triangle colors of the scenes, one per window in the order they open.
+const PALETTE: [(wgpu::Color, [f32; 4]); 4] = [
+    (
+        wgpu::Color {
+            r: 0.1,
+            g: 0.2,
+            b: 0.3,
+            a: 1.0,
+        },
+        [1.0, 0.6, 0.2, 1.0],
+    ),
+    (
+        wgpu::Color {
+            r: 0.3,
+            g: 0.1,
+            b: 0.1,
+            a: 1.0,
+        },
+        [0.3, 0.9, 0.6, 1.0],
+    ),
+    (
+        wgpu::Color {
+            r: 0.1,
+            g: 0.3,
+            b: 0.1,
+            a: 1.0,
+        },
+        [0.9, 0.3, 0.8, 1.0],
+    ),
+    (
+        wgpu::Color {
+            r: 0.2,
+            g: 0.2,
+            b: 0.2,
+            a: 1.0,
+        },
+        [0.9, 0.9, 0.3, 1.0],
+    ),
+];
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+struct SceneUniform {
+    tint: [f32; 4],
+    angle: f32,
+    _padding: [f32; 3],
+}
+
+/// What one window shows.
+struct Scene {
+    background_color: wgpu::Color,
+    uniform: SceneUniform,
+    /// Radians per second.
+    spin: f32,
+    uniform_buffer: Restorable<TrackedBuffer>,
+    bind_group: Restorable<wgpu::BindGroup>,
+}
+
+impl Scene {
+    fn new(
+        ctx: &GpuContext,
+        layout: &wgpu::BindGroupLayout,
+        index: usize,
+    ) -> wgpu_template::Result<Self> {
+        let (background_color, tint) = PALETTE[index % PALETTE.len()];
+        let uniform = SceneUniform {
+            tint,
+            angle: 0.0,
+            _padding: [0.0; 3],
+        };
+
+        let uniform_buffer = ctx.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Scene Buffer"),
+            contents: bytemuck::cast_slice(&[uniform]),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+
+        let bind_group = ctx.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("scene_bind_group"),
+            layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: uniform_buffer.get().as_entire_binding(),
+            }],
+        })?;
+
+        Ok(Self {
+            background_color,
+            uniform,
+            spin: 0.5 + index as f32 * 0.25,
+            uniform_buffer,
+            bind_group,
+        })
+    }
+}
+
+/// Several windows sharing one device: each shows its own scene, drawn with the same pipeline.
+pub struct Example {
+    /// `shader.wgsl`, reloaded when saved with `--hot-reload`.
+    shader: HotShader,
+    render_pipeline_layout: Restorable<wgpu::PipelineLayout>,
+    render_pipeline: Restorable<wgpu::RenderPipeline>,
+    scene_bind_group_layout: Restorable<wgpu::BindGroupLayout>,
+    /// Keyed by window, `None` being the offscreen target when headless.
+    scenes: HashMap<Option<WindowId>, Scene>,
+    windows_opened: usize,
+}
+
+impl Example {
+    /// Builds the pipeline for `shader`, again each time it is reloaded.
+    fn create_render_pipeline(
+        ctx: &GpuContext,
+        layout: &wgpu::PipelineLayout,
+        shader: &wgpu::ShaderModule,
+    ) -> wgpu_template::Result<Restorable<wgpu::RenderPipeline>> {
+        ctx.try_create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Render Pipeline"),
+            layout: Some(layout),
+            vertex: wgpu::VertexState {
+                module: shader,
+                entry_point: "vs_main",
+                buffers: &[],
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: shader,
+                entry_point: "fs_main",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: ctx.format,
+                    blend: Some(wgpu::BlendState::REPLACE),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                strip_index_format: None,
+                front_face: wgpu::FrontFace::Ccw,
+                cull_mode: None,
+                polygon_mode: wgpu::PolygonMode::Fill,
+                unclipped_depth: false,
+                conservative: false,
+            },
+            depth_stencil: Some(wgpu::DepthStencilState {
+                format: texture::Texture::DEPTH_FORMAT,
+                depth_write_enabled: true,
+                depth_compare: wgpu::CompareFunction::Less,
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState::default(),
+            }),
+            multisample: wgpu::MultisampleState {
+                count: 1,
+                mask: !0,
+                alpha_to_coverage_enabled: false,
+            },
+            multiview: None,
+        })
+    }
+}
+
+impl Application for Example {
+    fn init(ctx: &GpuContext) -> wgpu_template::Result<Self> {
+        let shader = HotShader::new(ctx, hot_wgsl!("shader.wgsl"))?;
+
+        let scene_bind_group_layout =
+            ctx.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                label: Some("scene_bind_group_layout"),
+                entries: &[wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::VERTEX,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                }],
+            });
+
+        let render_pipeline_layout =
+            ctx.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some("Render Pipeline Layout"),
+                bind_group_layouts: &[&scene_bind_group_layout.get()],
+                push_constant_ranges: &[],
+            })?;
+
+        let render_pipeline =
+            Self::create_render_pipeline(ctx, &render_pipeline_layout.get(), &shader.module())?;
+
+        let mut scenes = HashMap::new();
+        let scene = Scene::new(ctx, &scene_bind_group_layout.get(), 0)?;
+        scenes.insert(ctx.window, scene);
+
+        Ok(Self {
+            shader,
+            render_pipeline_layout,
+            render_pipeline,
+            scene_bind_group_layout,
+            scenes,
+            windows_opened: 1,
+        })
+    }
+
+    fn window_attributes() -> WindowAttributes {
+        Window::default_attributes()
+            .with_title("wgpu-template")
+            .with_inner_size(winit::dpi::LogicalSize::new(320.0, 280.0))
+    }
+
+    fn redraw_policy() -> RedrawPolicy {
+        RedrawPolicy::Continuous
+    }
+
+    fn bindings() -> Bindings {
+        // Not W, which moves forward by default.
+        Bindings::default()
+            .bind(OPEN_WINDOW, [Binding::Key(KeyCode::KeyN)])
+            .bind(CLOSE_WINDOW, [Binding::Key(KeyCode::KeyC)])
+    }
+
+    fn depth_format() -> Option<wgpu::TextureFormat> {
+        Some(texture::Texture::DEPTH_FORMAT)
+    }
+
+    fn window_opened(&mut self, ctx: &GpuContext, window: WindowId) {
+        match Scene::new(
+            ctx,
+            &self.scene_bind_group_layout.get(),
+            self.windows_opened,
+        ) {
+            Ok(scene) => {
+                self.scenes.insert(Some(window), scene);
+            }
+            Err(e) => log::error!("{e}, the window stays empty"),
+        }
+        self.windows_opened += 1;
+    }
+
+    fn window_closed(&mut self, _ctx: &GpuContext, window: WindowId) {
+        self.scenes.remove(&Some(window));
+    }
+
+    fn input(&mut self, ctx: &GpuContext, event: &WindowEvent) -> bool {
+        match event {
+            _ if ctx.input.bindings().is_triggered(OPEN_WINDOW, event) => {
+                ctx.open_window(
+                    Self::window_attributes()
+                        .with_title(format!("wgpu-template #{}", self.windows_opened + 1)),
+                );
+                true
+            }
+            _ if ctx.input.bindings().is_triggered(CLOSE_WINDOW, event) => {
+                if let Some(window) = ctx.window {
+                    ctx.close_window(window);
+                }
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn reload_shaders(&mut self, ctx: &GpuContext) {
+        let layout = self.render_pipeline_layout.get();
+        let rebuilt = self.shader.reload_with(ctx, |shader| {
+            Self::create_render_pipeline(ctx, &layout, shader)
+        });
+        if let Some(render_pipeline) = rebuilt {
+            self.render_pipeline = render_pipeline;
+        }
+    }
+
+    fn update(&mut self, _ctx: &GpuContext, dt: Duration) {
+        for scene in self.scenes.values_mut() {
+            scene.uniform.angle += scene.spin * dt.as_secs_f32();
+        }
+    }
+
+    fn render(&mut self, frame: &mut Frame) {
+        let Some(scene) = self.scenes.get(&frame.window) else {
+            return;
+        };
+        frame.queue.write_buffer(
+            &scene.uniform_buffer.get(),
+            0,
+            bytemuck::cast_slice(&[scene.uniform]),
+        );
+
+        let render_pipeline = self.render_pipeline.get();
+        let bind_group = scene.bind_group.get();
+
+        let mut render_pass = frame
+            .encoder
+            .begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Render Pass"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: frame.view,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(scene.background_color),
+                        store: wgpu::StoreOp::Store,
+                    },
+                })],
+                depth_stencil_attachment: frame.depth_view.map(|view| {
+                    wgpu::RenderPassDepthStencilAttachment {
+                        view,
+                        depth_ops: Some(wgpu::Operations {
+                            load: wgpu::LoadOp::Clear(1.0),
+                            store: wgpu::StoreOp::Store,
+                        }),
+                        stencil_ops: None,
+                    }
+                }),
+                occlusion_query_set: None,
+                timestamp_writes: frame.profiler.render_pass_timestamps("Render Pass"),
+            });
+
+        render_pass.set_pipeline(&render_pipeline);
+        render_pass.set_bind_group(0, &bind_group, &[]);
+        render_pass.draw(0..3, 0..2);
+    }
+}
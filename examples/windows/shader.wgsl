@@ -0,0 +1,39 @@
+struct Scene {
+    tint: vec4<f32>,
+    angle: f32,
+};
+@group(0) @binding(0)
+var<uniform> scene: Scene;
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) color: vec3<f32>,
+};
+
+@vertex
+fn vs_main(
+    @builtin(vertex_index) in_vertex_index: u32,
+    @builtin(instance_index) in_instance_index: u32,
+) -> VertexOutput {
+    let x = f32(1 - i32(in_vertex_index)) * 0.6;
+    let y = f32(i32(in_vertex_index & 1u) * 2 - 1) * 0.6;
+
+    // The second triangle spins the other way and lies further back, so the depth buffer
+    // decides which one is in front.
+    let direction = 1.0 - 2.0 * f32(in_instance_index);
+    let angle = scene.angle * direction;
+    let position = vec2<f32>(
+        x * cos(angle) - y * sin(angle),
+        x * sin(angle) + y * cos(angle),
+    );
+
+    var out: VertexOutput;
+    out.clip_position = vec4<f32>(position, 0.25 + 0.5 * f32(in_instance_index), 1.0);
+    out.color = scene.tint.rgb * (1.0 - 0.5 * f32(in_instance_index));
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    return vec4<f32>(in.color, 1.0);
+}
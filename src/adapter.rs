@@ -0,0 +1,118 @@
+use std::fmt;
+
+/// Criteria an adapter must meet to be picked. Unset criteria match every adapter.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct AdapterSelector {
+    /// Case-insensitive substring of the adapter name, e.g. `"nvidia"` or `"llvmpipe"`.
+    pub name: Option<String>,
+    pub backend: Option<wgpu::Backend>,
+    pub device_type: Option<wgpu::DeviceType>,
+}
+
+impl AdapterSelector {
+    pub fn is_empty(&self) -> bool {
+        self.name.is_none() && self.backend.is_none() && self.device_type.is_none()
+    }
+
+    pub fn matches(&self, info: &wgpu::AdapterInfo) -> bool {
+        let name_matches = self
+            .name
+            .as_ref()
+            .is_none_or(|name| info.name.to_lowercase().contains(&name.to_lowercase()));
+
+        name_matches
+            && self.backend.is_none_or(|backend| info.backend == backend)
+            && self
+                .device_type
+                .is_none_or(|device_type| info.device_type == device_type)
+    }
+}
+
+impl fmt::Display for AdapterSelector {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut criteria = Vec::new();
+        if let Some(name) = &self.name {
+            criteria.push(format!("name contains {name:?}"));
+        }
+        if let Some(backend) = self.backend {
+            criteria.push(format!("backend {backend:?}"));
+        }
+        if let Some(device_type) = self.device_type {
+            criteria.push(format!("device type {device_type:?}"));
+        }
+        if criteria.is_empty() {
+            write!(f, "any adapter")
+        } else {
+            write!(f, "{}", criteria.join(", "))
+        }
+    }
+}
+
+/// What an adapter is and what it can do.
+#[derive(Clone, Debug)]
+pub struct AdapterDetails {
+    pub info: wgpu::AdapterInfo,
+    pub features: wgpu::Features,
+    pub limits: wgpu::Limits,
+}
+
+impl AdapterDetails {
+    pub fn new(adapter: &wgpu::Adapter) -> Self {
+        Self {
+            info: adapter.get_info(),
+            features: adapter.features(),
+            limits: adapter.limits(),
+        }
+    }
+}
+
+impl fmt::Display for AdapterDetails {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let info = &self.info;
+        writeln!(
+            f,
+            "{} ({:?}, {:?})",
+            info.name, info.backend, info.device_type
+        )?;
+        writeln!(
+            f,
+            "  vendor: {:#06x}, device: {:#06x}, driver: {} {}",
+            info.vendor, info.device, info.driver, info.driver_info
+        )?;
+        writeln!(f, "  features: {:?}", self.features)?;
+        write!(f, "  limits: {:?}", self.limits)
+    }
+}
+
+/// Lists every adapter available on `backends`.
+pub fn enumerate_adapters(backends: wgpu::Backends) -> Vec<AdapterDetails> {
+    let instance = wgpu::Instance::new(wgpu::InstanceDescriptor {
+        backends,
+        ..Default::default()
+    });
+
+    instance
+        .enumerate_adapters(backends)
+        .iter()
+        .map(AdapterDetails::new)
+        .collect()
+}
+
+/// Prints every adapter available on `backends`, marking those that `selector` accepts.
+pub fn print_adapters(backends: wgpu::Backends, selector: &AdapterSelector) {
+    let adapters = enumerate_adapters(backends);
+    if adapters.is_empty() {
+        println!("No adapters found for {backends:?}");
+        return;
+    }
+
+    for (i, adapter) in adapters.iter().enumerate() {
+        let marker = if selector.matches(&adapter.info) {
+            "*"
+        } else {
+            " "
+        };
+        println!("{marker} [{i}] {adapter}");
+    }
+    println!("Adapters marked with * match {selector}");
+}
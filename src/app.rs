@@ -0,0 +1,167 @@
+use crate::{
+    Bindings, DrawCounts, FrameStats, GpuContext, GpuProfiler, RendererConfig, Result, StatsReport,
+};
+use std::time::Duration;
+use winit::event::WindowEvent;
+use winit::window::{Window, WindowAttributes, WindowId};
+
+/// The rendering code of an app. The runner owns the window, surface and event loop, and calls
+/// these hooks.
+pub trait Application: Sized + 'static {
+    /// Creates the app's GPU resources. An error stops the runner and is returned from
+    /// [`run`](crate::run).
+    fn init(ctx: &GpuContext) -> Result<Self>;
+
+    fn window_attributes() -> WindowAttributes {
+        Window::default_attributes()
+            .with_title("wgpu-template")
+            .with_inner_size(winit::dpi::LogicalSize::new(640.0, 480.0))
+    }
+
+    /// The renderer settings the app starts from. Environment variables and command-line
+    /// arguments are applied on top, see [`RendererConfig`].
+    fn renderer_config() -> RendererConfig {
+        RendererConfig::default()
+    }
+
+    /// When the runner renders frames.
+    fn redraw_policy() -> RedrawPolicy {
+        RedrawPolicy::OnDemand
+    }
+
+    /// Whether the runner reports frame statistics to the log or the window title. Off by
+    /// default; the statistics are still available from [`Frame::stats`].
+    fn stats_report() -> StatsReport {
+        StatsReport::default()
+    }
+
+    /// The actions the app and its camera controllers look up in `ctx.input`, and their default
+    /// keys. Users can override them with a file, see [`Bindings`].
+    fn bindings() -> Bindings {
+        Bindings::default()
+    }
+
+    /// Handles a window event. Returns `true` if the event was consumed and a redraw is needed.
+    fn input(&mut self, _ctx: &GpuContext, _event: &WindowEvent) -> bool {
+        false
+    }
+
+    /// How often `update` runs. The runner calls it at this fixed rate whatever the frame rate
+    /// is, running several updates for a slow frame and none for a fast one.
+    fn timestep() -> Duration {
+        Duration::from_secs(1) / 60
+    }
+
+    /// Advances the simulation by `dt`, which is always [`Application::timestep`] when driven by
+    /// the runner.
+    fn update(&mut self, _ctx: &GpuContext, _dt: Duration) {}
+
+    fn render(&mut self, frame: &mut Frame);
+
+    /// Called after the color target was resized to `ctx.size`.
+    fn resize(&mut self, _ctx: &GpuContext) {}
+
+    /// Called between frames after a file watched by one of the app's
+    /// [`HotShader`](crate::HotShader)s changed. Apps call [`HotShader::reload`](crate::HotShader::reload)
+    /// and rebuild the pipelines of the shaders that compiled; the runner only renders a new
+    /// frame if one did.
+    fn reload_shaders(&mut self, _ctx: &GpuContext) {}
+
+    /// Called after the device was lost and replaced by a new one, once the
+    /// [`Restorable`](crate::Restorable) resources the app created through the [`GpuContext`]
+    /// were created again on it, from the descriptors and contents the context kept. The app's
+    /// other state is untouched.
+    ///
+    /// Override it to restore what the context does not keep: resources created directly on the
+    /// old device, which are unusable, and contents written into buffers and textures since they
+    /// were created, unless the app writes them again every frame. The default does nothing.
+    fn recreate(&mut self, _ctx: &GpuContext) -> Result<()> {
+        Ok(())
+    }
+
+    /// The format of the depth buffer the runner keeps for every window, and passes to `render`
+    /// as [`Frame::depth_view`]. `None`, the default, creates none.
+    fn depth_format() -> Option<wgpu::TextureFormat> {
+        None
+    }
+
+    /// Called when a window opened with [`GpuContext::open_window`] is ready, before its first
+    /// frame. `ctx.window` and `ctx.size` describe it.
+    fn window_opened(&mut self, _ctx: &GpuContext, _window: WindowId) {}
+
+    /// Called after `window` was closed and its surface released.
+    fn window_closed(&mut self, _ctx: &GpuContext, _window: WindowId) {}
+}
+
+/// When the runner renders a new frame.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum RedrawPolicy {
+    /// Render frames back to back, as fast as presentation allows.
+    Continuous,
+    /// Only render when the window asks for it, after `input` returns `true`, or after the app
+    /// called [`GpuContext::request_redraw`]. The event loop sleeps in between, and a frame
+    /// after a sleep runs at most one update. Input replays and recordings render continuously.
+    OnDemand,
+    /// Render continuously, but at most this many frames per second. The event loop sleeps
+    /// between frames. The rate must be positive.
+    Capped(f64),
+}
+
+/// Everything `Application::render` needs to record a frame.
+pub struct Frame<'a> {
+    pub device: &'a wgpu::Device,
+    pub queue: &'a wgpu::Queue,
+    pub view: &'a wgpu::TextureView,
+    /// The depth buffer of the window, when [`Application::depth_format`] asks for one.
+    pub depth_view: Option<&'a wgpu::TextureView>,
+    /// The window this frame is for, `None` when headless. Apps with several windows pick the
+    /// scene to draw by it.
+    pub window: Option<WindowId>,
+    /// The `encoder` builds a command buffer that is submitted once `render` returns.
+    pub encoder: wgpu::CommandEncoder,
+    pub format: wgpu::TextureFormat,
+    pub size: winit::dpi::PhysicalSize<u32>,
+    /// How far this frame lies between the previous update and the latest one, in `0.0..=1.0`.
+    /// Rendering state interpolated by it moves smoothly even when updates and frames don't
+    /// line up.
+    pub alpha: f32,
+    /// Timings of the previous frames.
+    pub stats: &'a FrameStats,
+    /// Measures the GPU time of passes and scopes recorded into `encoder`.
+    pub profiler: &'a mut GpuProfiler,
+    /// Filled in by the [`InstrumentedRenderPass`](crate::InstrumentedRenderPass)es recorded
+    /// this frame.
+    pub draw_counts: DrawCounts,
+}
+
+impl<'a> Frame<'a> {
+    pub(crate) fn new(
+        ctx: &'a GpuContext,
+        view: &'a wgpu::TextureView,
+        depth_view: Option<&'a wgpu::TextureView>,
+        alpha: f32,
+        stats: &'a FrameStats,
+        profiler: &'a mut GpuProfiler,
+    ) -> Self {
+        let encoder = ctx
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("Render Encoder"),
+            });
+
+        Self {
+            device: &ctx.device,
+            queue: &ctx.queue,
+            view,
+            depth_view,
+            window: ctx.window,
+            encoder,
+            format: ctx.format,
+            size: ctx.size,
+            alpha,
+            stats,
+            profiler,
+            draw_counts: DrawCounts::default(),
+        }
+    }
+}
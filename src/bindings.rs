@@ -0,0 +1,201 @@
+use crate::InputEvent;
+use crate::{Error, Result};
+use serde::de::value::{Error as ValueError, StrDeserializer};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::Path;
+use std::str::FromStr;
+use winit::event::{MouseButton, WindowEvent};
+use winit::keyboard::KeyCode;
+
+/// The names of the actions the runner and the camera controllers use.
+pub mod action {
+    pub const QUIT: &str = "quit";
+    pub const SCREENSHOT: &str = "screenshot";
+    pub const MOVE_FORWARD: &str = "move_forward";
+    pub const MOVE_BACKWARD: &str = "move_backward";
+    pub const MOVE_LEFT: &str = "move_left";
+    pub const MOVE_RIGHT: &str = "move_right";
+    pub const MOVE_UP: &str = "move_up";
+    pub const MOVE_DOWN: &str = "move_down";
+    pub const TURN_LEFT: &str = "turn_left";
+    pub const TURN_RIGHT: &str = "turn_right";
+}
+
+/// A key or mouse button an action is bound to.
+///
+/// Written as the name of the [`KeyCode`], which is the key's position on a US QWERTY layout
+/// (`KeyW`, `ArrowUp`, `Space`), or as `MouseLeft`, `MouseRight`, `MouseMiddle`, `MouseBack`,
+/// `MouseForward` and `Mouse<n>` for the other buttons.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum Binding {
+    Key(KeyCode),
+    Mouse(MouseButton),
+}
+
+impl fmt::Display for Binding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Key(key) => write!(f, "{key:?}"),
+            Self::Mouse(MouseButton::Other(button)) => write!(f, "Mouse{button}"),
+            Self::Mouse(button) => write!(f, "Mouse{button:?}"),
+        }
+    }
+}
+
+impl FromStr for Binding {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let s = s.trim();
+        if let Some(button) = s.strip_prefix("Mouse") {
+            return Ok(Self::Mouse(match button {
+                "Left" => MouseButton::Left,
+                "Right" => MouseButton::Right,
+                "Middle" => MouseButton::Middle,
+                "Back" => MouseButton::Back,
+                "Forward" => MouseButton::Forward,
+                _ => MouseButton::Other(button.parse().map_err(|_| Error::UnknownBinding {
+                    name: s.to_string(),
+                })?),
+            }));
+        }
+        KeyCode::deserialize(StrDeserializer::<ValueError>::new(s))
+            .map(Self::Key)
+            .map_err(|_| Error::UnknownBinding {
+                name: s.to_string(),
+            })
+    }
+}
+
+impl TryFrom<String> for Binding {
+    type Error = Error;
+
+    fn try_from(s: String) -> Result<Self> {
+        s.parse()
+    }
+}
+
+impl From<Binding> for String {
+    fn from(binding: Binding) -> Self {
+        binding.to_string()
+    }
+}
+
+/// Named actions and the keys and mouse buttons that trigger them.
+///
+/// Apps and controllers ask [`InputState`](crate::InputState) about actions instead of keys, so
+/// the keys can be changed without touching them: the app starts from
+/// [`Application::bindings`](crate::Application::bindings), and the runner applies a RON or TOML
+/// file of overrides given with `--bindings` or `WGPU_BINDINGS`. `--list-bindings` prints the
+/// result.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Bindings {
+    actions: BTreeMap<String, Vec<Binding>>,
+}
+
+impl Default for Bindings {
+    /// Escape to quit, F12 for a screenshot, and the movement keys of the camera controllers.
+    fn default() -> Self {
+        use action::*;
+        use KeyCode::*;
+
+        let keys = |keys: &[KeyCode]| keys.iter().copied().map(Binding::Key).collect::<Vec<_>>();
+        Self::new()
+            .bind(QUIT, keys(&[Escape]))
+            .bind(SCREENSHOT, keys(&[F12]))
+            .bind(MOVE_FORWARD, keys(&[KeyW, ArrowUp]))
+            .bind(MOVE_BACKWARD, keys(&[KeyS, ArrowDown]))
+            .bind(MOVE_LEFT, keys(&[KeyA]))
+            .bind(MOVE_RIGHT, keys(&[KeyD]))
+            .bind(MOVE_UP, keys(&[Space]))
+            .bind(MOVE_DOWN, keys(&[ShiftLeft, ShiftRight]))
+            .bind(TURN_LEFT, keys(&[ArrowLeft]))
+            .bind(TURN_RIGHT, keys(&[ArrowRight]))
+    }
+}
+
+impl Bindings {
+    /// No actions at all.
+    pub fn new() -> Self {
+        Self {
+            actions: BTreeMap::new(),
+        }
+    }
+
+    /// Binds `action` to `bindings`, replacing what it was bound to.
+    pub fn bind(
+        mut self,
+        action: impl Into<String>,
+        bindings: impl IntoIterator<Item = Binding>,
+    ) -> Self {
+        self.actions
+            .insert(action.into(), bindings.into_iter().collect());
+        self
+    }
+
+    /// What `action` is bound to, nothing if it is unknown.
+    pub fn get(&self, action: &str) -> &[Binding] {
+        self.actions.get(action).map_or(&[], Vec::as_slice)
+    }
+
+    /// The actions and their bindings, sorted by name.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &[Binding])> {
+        self.actions
+            .iter()
+            .map(|(action, bindings)| (action.as_str(), bindings.as_slice()))
+    }
+
+    /// Takes the bindings of the actions in `overrides`, keeping those of the others.
+    pub fn merge(&mut self, overrides: Bindings) {
+        self.actions.extend(overrides.actions);
+    }
+
+    /// Reads bindings from RON (`.ron`) or TOML (`.toml`), as a map from action names to lists of
+    /// [`Binding`]s, e.g. `move_forward = ["KeyZ", "ArrowUp"]`.
+    pub fn load(path: &Path) -> Result<Self> {
+        let text = std::fs::read_to_string(path).map_err(|e| Error::read(path, e))?;
+        let actions = match path.extension().and_then(|extension| extension.to_str()) {
+            Some("ron") => ron::from_str(&text).map_err(|e| Error::read(path, e))?,
+            Some("toml") => toml::from_str(&text).map_err(|e| Error::read(path, e))?,
+            _ => return Err(Error::read(path, "not a .ron or .toml file")),
+        };
+        Ok(Self { actions })
+    }
+
+    /// Whether `event` presses a key or button bound to `action`. Key repeats don't.
+    ///
+    /// This is for reacting to an event right away, in [`Application::input`]. During `update`,
+    /// ask [`InputState`](crate::InputState) instead.
+    ///
+    /// [`Application::input`]: crate::Application::input
+    pub fn is_triggered(&self, action: &str, event: &WindowEvent) -> bool {
+        let binding = match InputEvent::from_window_event(event) {
+            Some(InputEvent::Key { key, pressed: true }) => Binding::Key(key),
+            Some(InputEvent::MouseButton {
+                button,
+                pressed: true,
+            }) => Binding::Mouse(button),
+            _ => return false,
+        };
+        self.get(action).contains(&binding)
+    }
+}
+
+impl fmt::Display for Bindings {
+    /// One action per line, with its bindings.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let width = self.actions.keys().map(String::len).max().unwrap_or(0);
+        for (action, bindings) in self.iter() {
+            let bindings = bindings
+                .iter()
+                .map(Binding::to_string)
+                .collect::<Vec<_>>()
+                .join(", ");
+            writeln!(f, "{action:<width$}  {bindings}")?;
+        }
+        Ok(())
+    }
+}
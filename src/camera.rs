@@ -0,0 +1,287 @@
+use crate::{action, InputState};
+use cgmath::{prelude::*, Matrix4, Point3, Quaternion, Rad, Vector3, Vector4};
+use std::time::Duration;
+
+pub const OPENGL_TO_WGPU_MATRIX: Matrix4<f32> = Matrix4::new(
+    1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 0.5, 0.5, 0.0, 0.0, 0.0, 1.0,
+);
+
+/// How view space is projected onto the screen. `fovy` is in degrees.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Projection {
+    Perspective {
+        fovy: f32,
+        aspect: f32,
+        znear: f32,
+        zfar: f32,
+    },
+    /// `height` is the extent of the view volume along the y axis; the width follows the aspect.
+    Orthographic {
+        height: f32,
+        aspect: f32,
+        znear: f32,
+        zfar: f32,
+    },
+    /// A perspective projection with the far plane at infinity, so distant geometry is never
+    /// clipped.
+    InfinitePerspective { fovy: f32, aspect: f32, znear: f32 },
+}
+
+impl Projection {
+    /// The projection matrix in wgpu clip space.
+    pub fn matrix(&self) -> Matrix4<f32> {
+        match *self {
+            Self::Perspective {
+                fovy,
+                aspect,
+                znear,
+                zfar,
+            } => {
+                OPENGL_TO_WGPU_MATRIX * cgmath::perspective(cgmath::Deg(fovy), aspect, znear, zfar)
+            }
+            Self::Orthographic {
+                height,
+                aspect,
+                znear,
+                zfar,
+            } => {
+                let top = height / 2.0;
+                let right = top * aspect;
+                OPENGL_TO_WGPU_MATRIX * cgmath::ortho(-right, right, -top, top, znear, zfar)
+            }
+            Self::InfinitePerspective {
+                fovy,
+                aspect,
+                znear,
+            } => {
+                // Maps depth straight to 0..1: `znear` to 0 and infinity to 1.
+                let f = 1.0 / (fovy.to_radians() / 2.0).tan();
+                Matrix4::from_cols(
+                    Vector4::new(f / aspect, 0.0, 0.0, 0.0),
+                    Vector4::new(0.0, f, 0.0, 0.0),
+                    Vector4::new(0.0, 0.0, -1.0, -1.0),
+                    Vector4::new(0.0, 0.0, -znear, 0.0),
+                )
+            }
+        }
+    }
+
+    pub fn aspect(&self) -> f32 {
+        match *self {
+            Self::Perspective { aspect, .. }
+            | Self::Orthographic { aspect, .. }
+            | Self::InfinitePerspective { aspect, .. } => aspect,
+        }
+    }
+
+    /// Updates the aspect ratio for a new viewport size. Zero-sized viewports are ignored.
+    pub fn resize(&mut self, width: u32, height: u32) {
+        if width == 0 || height == 0 {
+            return;
+        }
+        match self {
+            Self::Perspective { aspect, .. }
+            | Self::Orthographic { aspect, .. }
+            | Self::InfinitePerspective { aspect, .. } => {
+                *aspect = width as f32 / height as f32;
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Camera {
+    pub eye: Point3<f32>,
+    pub target: Point3<f32>,
+    pub up: Vector3<f32>,
+    pub projection: Projection,
+}
+
+impl Camera {
+    pub fn view_matrix(&self) -> Matrix4<f32> {
+        Matrix4::look_at_rh(self.eye, self.target, self.up)
+    }
+
+    pub fn build_view_projection_matrix(&self) -> Matrix4<f32> {
+        self.projection.matrix() * self.view_matrix()
+    }
+
+    /// The camera `alpha` of the way from `self` to `next`, for rendering between two updates.
+    /// The projection is taken from `next`.
+    pub fn interpolate(&self, next: &Camera, alpha: f32) -> Camera {
+        Camera {
+            eye: self.eye + (next.eye - self.eye) * alpha,
+            target: self.target + (next.target - self.target) * alpha,
+            up: self.up.lerp(next.up, alpha),
+            projection: next.projection,
+        }
+    }
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct CameraUniform {
+    view_proj: [[f32; 4]; 4],
+}
+
+impl Default for CameraUniform {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CameraUniform {
+    pub fn new() -> Self {
+        Self {
+            view_proj: Matrix4::identity().into(),
+        }
+    }
+
+    pub fn update_view_proj(&mut self, camera: &Camera) {
+        self.view_proj = camera.build_view_projection_matrix().into();
+    }
+}
+
+/// Moves a `Camera` in response to input or time. Controllers are interchangeable, so an
+/// application can hold a `Box<dyn CameraController>` and swap it at runtime.
+pub trait CameraController {
+    /// Called from `Application::update`, with the keyboard and mouse as of that update.
+    fn update_camera(&mut self, camera: &mut Camera, input: &InputState, dt: Duration);
+}
+
+/// Circles the camera around its target: `move_forward` and `move_backward` zoom, the left and
+/// right moves and turns circle. By default these are WASD and the arrow keys. `speed` is in
+/// units per second.
+pub struct OrbitController {
+    pub speed: f32,
+}
+
+impl OrbitController {
+    pub fn new(speed: f32) -> Self {
+        Self { speed }
+    }
+}
+
+impl CameraController for OrbitController {
+    fn update_camera(&mut self, camera: &mut Camera, input: &InputState, dt: Duration) {
+        let speed = self.speed * dt.as_secs_f32();
+        let is_pressed =
+            |first, second| input.is_action_pressed(first) || input.is_action_pressed(second);
+
+        let forward = camera.target - camera.eye;
+        let forward_norm = forward.normalize();
+        let forward_mag = forward.magnitude();
+
+        // Prevents glitching when the camera gets too close to the
+        // center of the scene.
+        if input.is_action_pressed(action::MOVE_FORWARD) && forward_mag > speed {
+            camera.eye += forward_norm * speed;
+        }
+        if input.is_action_pressed(action::MOVE_BACKWARD) {
+            camera.eye -= forward_norm * speed;
+        }
+
+        let right = forward_norm.cross(camera.up);
+
+        // Redo radius calc in case the forward/backward is pressed.
+        let forward = camera.target - camera.eye;
+        let forward_mag = forward.magnitude();
+
+        if is_pressed(action::MOVE_RIGHT, action::TURN_RIGHT) {
+            // Rescale the distance between the target and the eye so
+            // that it doesn't change. The eye, therefore, still
+            // lies on the circle made by the target and eye.
+            camera.eye = camera.target - (forward + right * speed).normalize() * forward_mag;
+        }
+        if is_pressed(action::MOVE_LEFT, action::TURN_LEFT) {
+            camera.eye = camera.target - (forward - right * speed).normalize() * forward_mag;
+        }
+    }
+}
+
+/// Moves the camera freely with the `move_*` actions and turns it with `turn_left` and
+/// `turn_right`: by default WASD to move, Space and Shift to rise and sink, and the left and
+/// right arrows to turn. `speed` is in units per second, `turn_speed` in radians per second.
+pub struct FlyController {
+    pub speed: f32,
+    pub turn_speed: f32,
+}
+
+impl FlyController {
+    pub fn new(speed: f32, turn_speed: f32) -> Self {
+        Self { speed, turn_speed }
+    }
+}
+
+impl CameraController for FlyController {
+    fn update_camera(&mut self, camera: &mut Camera, input: &InputState, dt: Duration) {
+        let up = camera.up.normalize();
+        let dt = dt.as_secs_f32();
+
+        let mut turn = 0.0;
+        if input.is_action_pressed(action::TURN_LEFT) {
+            turn += self.turn_speed * dt;
+        }
+        if input.is_action_pressed(action::TURN_RIGHT) {
+            turn -= self.turn_speed * dt;
+        }
+        let forward =
+            Quaternion::from_axis_angle(up, Rad(turn)).rotate_vector(camera.target - camera.eye);
+
+        let forward_norm = forward.normalize();
+        let right = forward_norm.cross(up).normalize();
+
+        let mut movement = Vector3::zero();
+        if input.is_action_pressed(action::MOVE_FORWARD) {
+            movement += forward_norm;
+        }
+        if input.is_action_pressed(action::MOVE_BACKWARD) {
+            movement -= forward_norm;
+        }
+        if input.is_action_pressed(action::MOVE_RIGHT) {
+            movement += right;
+        }
+        if input.is_action_pressed(action::MOVE_LEFT) {
+            movement -= right;
+        }
+        if input.is_action_pressed(action::MOVE_UP) {
+            movement += up;
+        }
+        if input.is_action_pressed(action::MOVE_DOWN) {
+            movement -= up;
+        }
+
+        camera.eye += movement * self.speed * dt;
+        camera.target = camera.eye + forward;
+    }
+}
+
+/// A camera path, called with the total time elapsed.
+pub type CameraScript = dyn FnMut(Duration, &mut Camera);
+
+/// Drives the camera from a function of the time elapsed since the controller was created,
+/// e.g. for fly-throughs and recordings. Input is ignored.
+pub struct ScriptedController {
+    elapsed: Duration,
+    script: Box<CameraScript>,
+}
+
+impl ScriptedController {
+    pub fn new(script: impl FnMut(Duration, &mut Camera) + 'static) -> Self {
+        Self {
+            elapsed: Duration::ZERO,
+            script: Box::new(script),
+        }
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+}
+
+impl CameraController for ScriptedController {
+    fn update_camera(&mut self, camera: &mut Camera, _input: &InputState, dt: Duration) {
+        self.elapsed += dt;
+        (self.script)(self.elapsed, camera);
+    }
+}
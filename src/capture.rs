@@ -0,0 +1,148 @@
+use crate::{Error, Result};
+
+/// A pending copy of a color texture into a mappable staging buffer.
+///
+/// The copy is recorded into an encoder with [`FrameCapture::copy_from`], and the pixels can be
+/// read back with [`FrameCapture::read`] once that encoder has been submitted.
+pub struct FrameCapture {
+    buffer: wgpu::Buffer,
+    width: u32,
+    height: u32,
+    padded_bytes_per_row: u32,
+    format: wgpu::TextureFormat,
+}
+
+impl FrameCapture {
+    /// Records a copy of `texture` into a new staging buffer.
+    ///
+    /// The texture must have been created with `TextureUsages::COPY_SRC`.
+    pub fn copy_from(
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        texture: &wgpu::Texture,
+    ) -> Result<Self> {
+        let format = texture.format();
+        if !is_supported_format(format) {
+            return Err(Error::Capture {
+                message: format!("texture format {format:?} is not supported"),
+            });
+        }
+        if !texture.usage().contains(wgpu::TextureUsages::COPY_SRC) {
+            return Err(Error::Capture {
+                message: "the texture lacks COPY_SRC usage".to_string(),
+            });
+        }
+
+        let width = texture.width();
+        let height = texture.height();
+
+        // `bytes_per_row` of a texture-to-buffer copy has to be a multiple of 256.
+        let unpadded_bytes_per_row = width * 4;
+        let align = wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+        let padded_bytes_per_row = unpadded_bytes_per_row.div_ceil(align) * align;
+
+        let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Frame Capture Buffer"),
+            size: (padded_bytes_per_row * height) as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+
+        encoder.copy_texture_to_buffer(
+            wgpu::ImageCopyTexture {
+                texture,
+                mip_level: 0,
+                origin: wgpu::Origin3d::ZERO,
+                aspect: wgpu::TextureAspect::All,
+            },
+            wgpu::ImageCopyBuffer {
+                buffer: &buffer,
+                layout: wgpu::ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: Some(padded_bytes_per_row),
+                    rows_per_image: Some(height),
+                },
+            },
+            texture.size(),
+        );
+
+        Ok(Self {
+            buffer,
+            width,
+            height,
+            padded_bytes_per_row,
+            format,
+        })
+    }
+
+    /// Maps the staging buffer and returns its pixels as RGBA.
+    ///
+    /// Blocks until the GPU has finished the copy. The bytes are returned as they are stored in
+    /// the texture, so an sRGB target yields sRGB-encoded pixels, which is what PNG expects.
+    pub fn read(self, device: &wgpu::Device) -> Result<image::RgbaImage> {
+        let slice = self.buffer.slice(..);
+        let (sender, receiver) = std::sync::mpsc::channel();
+        slice.map_async(wgpu::MapMode::Read, move |result| {
+            let _ = sender.send(result);
+        });
+        device.poll(wgpu::Maintain::Wait);
+        let mapped = receiver
+            .recv()
+            .map_err(|e| e.to_string())
+            .and_then(|result| result.map_err(|e| e.to_string()));
+        if let Err(message) = mapped {
+            return Err(Error::Capture { message });
+        }
+
+        let unpadded_bytes_per_row = (self.width * 4) as usize;
+        let mut pixels = Vec::with_capacity(unpadded_bytes_per_row * self.height as usize);
+        {
+            let data = slice.get_mapped_range();
+            for row in data.chunks(self.padded_bytes_per_row as usize) {
+                pixels.extend_from_slice(&row[..unpadded_bytes_per_row]);
+            }
+        }
+        self.buffer.unmap();
+
+        if is_bgra(self.format) {
+            for pixel in pixels.chunks_exact_mut(4) {
+                pixel.swap(0, 2);
+            }
+        }
+
+        image::RgbaImage::from_raw(self.width, self.height, pixels).ok_or_else(|| Error::Capture {
+            message: "the captured frame has an unexpected size".to_string(),
+        })
+    }
+}
+
+/// Copies `texture` back to the CPU and returns it as an image.
+pub fn read_texture(
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    texture: &wgpu::Texture,
+) -> Result<image::RgbaImage> {
+    let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+        label: Some("Frame Capture Encoder"),
+    });
+    let capture = FrameCapture::copy_from(device, &mut encoder, texture)?;
+    queue.submit(std::iter::once(encoder.finish()));
+    capture.read(device)
+}
+
+fn is_supported_format(format: wgpu::TextureFormat) -> bool {
+    matches!(
+        format,
+        wgpu::TextureFormat::Rgba8Unorm
+            | wgpu::TextureFormat::Rgba8UnormSrgb
+            | wgpu::TextureFormat::Bgra8Unorm
+            | wgpu::TextureFormat::Bgra8UnormSrgb
+    )
+}
+
+fn is_bgra(format: wgpu::TextureFormat) -> bool {
+    matches!(
+        format,
+        wgpu::TextureFormat::Bgra8Unorm | wgpu::TextureFormat::Bgra8UnormSrgb
+    )
+}
@@ -0,0 +1,958 @@
+use crate::{AdapterSelector, Bindings, Error, Recording, Result};
+use log::{info, warn};
+use std::path::PathBuf;
+
+/// How the renderer picks its backend, adapter and device, and how it presents frames.
+///
+/// Start from [`RendererConfig::default`] (or [`RendererConfig::headless`]) and adjust it with the
+/// builder methods. [`RendererConfig::with_overrides`] then applies environment variables and
+/// command-line arguments on top, so a value can be forced without recompiling:
+///
+/// | Environment variable          | Argument                | Values                                                     |
+/// |-------------------------------|-------------------------|------------------------------------------------------------|
+/// | `WGPU_BACKEND`                | `--backend`             | comma-separated `vulkan`, `metal`, `dx12`, `gl`, `webgpu`, `primary`, `secondary`, `all` |
+/// | `WGPU_POWER_PREF`             | `--power`               | `low`, `high`, `none`                                      |
+/// | `WGPU_ADAPTER_NAME`           | `--adapter`             | part of an adapter name, case-insensitive                  |
+/// | `WGPU_DEVICE_TYPE`            | `--device-type`         | `discrete`, `integrated`, `virtual`, `cpu`, `other`        |
+/// | `WGPU_FORCE_FALLBACK_ADAPTER` | `--fallback-adapter`    | `1`/`true` (the argument takes no value)                   |
+/// | `WGPU_LIMITS`                 | `--limits`              | `default`, `downlevel`, `webgl2`                           |
+/// | `WGPU_PRESENT_MODE`           | `--present-mode`        | `auto-vsync`, `auto-no-vsync`, `fifo`, `fifo-relaxed`, `mailbox`, `immediate` |
+/// | `WGPU_FRAME_LATENCY`          | `--frame-latency`       | a positive integer                                         |
+/// | `WGPU_GPU_TRACE`              | `--gpu-trace`           | a path to write a Chrome trace of the GPU timings to on exit |
+/// | `WGPU_SCREENSHOT_DIR`         | `--screenshot-dir`      | the directory screenshots are saved in                     |
+/// | `WGPU_RECORD`                 | `--record`              | a `.gif` or `.png` (APNG) file, or a directory for numbered PNGs, to record to |
+/// | `WGPU_RECORD_FRAMES`          | `--record-frames`       | the number of frames to record                             |
+/// | `WGPU_RECORD_FPS`             | `--record-fps`          | the simulated frame rate of the recording                  |
+/// | `WGPU_BINDINGS`               | `--bindings`            | a `.ron` or `.toml` file of key bindings, see [`Bindings`] |
+/// | `WGPU_RECORD_INPUT`           | `--record-input`        | a `.ron` file to record the input to on exit               |
+/// | `WGPU_REPLAY_INPUT`           | `--replay-input`        | a `.ron` file of recorded input to replay                  |
+/// | `WGPU_HOT_RELOAD`             | `--hot-reload`          | `1`/`true` to reload shaders from disk, see [`HotShader`](crate::HotShader) (the argument takes no value) |
+///
+/// Arguments take precedence over environment variables. Run with `--list-adapters` to see the
+/// adapters that can be selected, and with `--list-bindings` to see the key bindings.
+#[derive(Clone, Debug)]
+pub struct RendererConfig {
+    pub(crate) backends: wgpu::Backends,
+    pub(crate) power_preference: wgpu::PowerPreference,
+    pub(crate) force_fallback_adapter: bool,
+    pub(crate) adapter: AdapterSelector,
+    pub(crate) required_features: wgpu::Features,
+    pub(crate) optional_features: wgpu::Features,
+    pub(crate) required_limits: wgpu::Limits,
+    pub(crate) optional_limits: Option<wgpu::Limits>,
+    pub(crate) present_mode: Option<wgpu::PresentMode>,
+    pub(crate) desired_maximum_frame_latency: u32,
+    pub(crate) gpu_profiling: bool,
+    pub(crate) gpu_trace: Option<PathBuf>,
+    pub(crate) screenshot_dir: PathBuf,
+    pub(crate) record: Option<Recording>,
+    pub(crate) bindings: Option<PathBuf>,
+    pub(crate) record_input: Option<PathBuf>,
+    pub(crate) replay_input: Option<PathBuf>,
+    pub(crate) hot_reload: bool,
+}
+
+impl Default for RendererConfig {
+    fn default() -> Self {
+        Self {
+            backends: wgpu::Backends::PRIMARY,
+            power_preference: wgpu::PowerPreference::default(),
+            force_fallback_adapter: false,
+            adapter: AdapterSelector::default(),
+            required_features: wgpu::Features::empty(),
+            optional_features: wgpu::Features::empty(),
+            required_limits: wgpu::Limits::default(),
+            optional_limits: None,
+            present_mode: None,
+            desired_maximum_frame_latency: 2,
+            gpu_profiling: false,
+            gpu_trace: None,
+            screenshot_dir: PathBuf::from("screenshots"),
+            record: None,
+            bindings: None,
+            record_input: None,
+            replay_input: None,
+            hot_reload: false,
+        }
+    }
+}
+
+impl RendererConfig {
+    /// Defaults for rendering without a window: every backend is allowed, so a software
+    /// adapter can be used on machines without a GPU.
+    pub fn headless() -> Self {
+        Self {
+            backends: wgpu::Backends::all(),
+            ..Default::default()
+        }
+    }
+
+    pub fn backends(mut self, backends: wgpu::Backends) -> Self {
+        self.backends = backends;
+        self
+    }
+
+    pub fn power_preference(mut self, power_preference: wgpu::PowerPreference) -> Self {
+        self.power_preference = power_preference;
+        self
+    }
+
+    /// Only accept a fallback (software) adapter.
+    pub fn force_fallback_adapter(mut self, force_fallback_adapter: bool) -> Self {
+        self.force_fallback_adapter = force_fallback_adapter;
+        self
+    }
+
+    /// Picks the first adapter `selector` accepts instead of letting wgpu choose one. Use the
+    /// backend to restrict the selection to one API. When no adapter matches, creating the
+    /// renderer fails with [`Error::NoAdapter`] rather than running on another one.
+    pub fn adapter(mut self, selector: AdapterSelector) -> Self {
+        self.adapter = selector;
+        self
+    }
+
+    /// Features the device must have. Creating the device fails if the adapter lacks any of them.
+    pub fn required_features(mut self, features: wgpu::Features) -> Self {
+        self.required_features = features;
+        self
+    }
+
+    /// Features that are enabled when the adapter supports them. Check which ones were granted
+    /// with [`GpuContext::has_features`](crate::GpuContext::has_features).
+    pub fn optional_features(mut self, features: wgpu::Features) -> Self {
+        self.optional_features = features;
+        self
+    }
+
+    /// Limits the device must support. Creating the device fails if the adapter's are lower.
+    pub fn required_limits(mut self, limits: wgpu::Limits) -> Self {
+        self.required_limits = limits;
+        self
+    }
+
+    /// Better limits to use when the adapter supports all of them. Otherwise the required
+    /// limits are used.
+    pub fn optional_limits(mut self, limits: wgpu::Limits) -> Self {
+        self.optional_limits = Some(limits);
+        self
+    }
+
+    /// The present mode to use if the surface supports it. Without one, the first mode the
+    /// surface reports is used.
+    pub fn present_mode(mut self, present_mode: wgpu::PresentMode) -> Self {
+        self.present_mode = Some(present_mode);
+        self
+    }
+
+    pub fn frame_latency(mut self, desired_maximum_frame_latency: u32) -> Self {
+        self.desired_maximum_frame_latency = desired_maximum_frame_latency;
+        self
+    }
+
+    /// Requests the timestamp query features the [`GpuProfiler`](crate::GpuProfiler) needs, as
+    /// optional features.
+    pub fn gpu_profiling(mut self, enabled: bool) -> Self {
+        self.gpu_profiling = enabled;
+        self
+    }
+
+    /// Enables GPU profiling and makes the runner write the timings as a Chrome trace to `path`
+    /// when it exits.
+    pub fn gpu_trace(mut self, path: impl Into<PathBuf>) -> Self {
+        self.gpu_profiling = true;
+        self.gpu_trace = Some(path.into());
+        self
+    }
+
+    /// Where the runner saves screenshots, `screenshots` in the working directory by default.
+    pub fn screenshot_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.screenshot_dir = dir.into();
+        self
+    }
+
+    /// Makes the runner record the first window from its first frame on. While recording, the
+    /// simulation advances by the recording's frame time per frame instead of the real time.
+    pub fn record(mut self, recording: Recording) -> Self {
+        self.record = Some(recording);
+        self
+    }
+
+    /// A RON or TOML file whose bindings replace the app's for the actions it lists.
+    pub fn bindings_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.bindings = Some(path.into());
+        self
+    }
+
+    /// Makes the runner record the input from the start and write it to `path` when it exits,
+    /// see [`InputRecording`](crate::InputRecording).
+    pub fn record_input(mut self, path: impl Into<PathBuf>) -> Self {
+        self.record_input = Some(path.into());
+        self
+    }
+
+    /// Makes the runner replay the input recorded in `path` from the start, instead of the
+    /// input of the windows.
+    pub fn replay_input(mut self, path: impl Into<PathBuf>) -> Self {
+        self.replay_input = Some(path.into());
+        self
+    }
+
+    /// Loads shaders made with [`hot_wgsl!`](crate::hot_wgsl) from disk and reloads them when
+    /// they are saved, for development. See [`HotShader`](crate::HotShader).
+    pub fn hot_reload(mut self, hot_reload: bool) -> Self {
+        self.hot_reload = hot_reload;
+        self
+    }
+
+    /// `defaults` with the bindings file applied. A file that can't be read is reported and
+    /// ignored.
+    pub(crate) fn bindings(&self, mut defaults: Bindings) -> Bindings {
+        if let Some(path) = &self.bindings {
+            match Bindings::load(path) {
+                Ok(overrides) => defaults.merge(overrides),
+                Err(e) => warn!("{e:#}, keeping the default bindings"),
+            }
+        }
+        defaults
+    }
+
+    /// Applies the environment variables and the process's command-line arguments.
+    pub fn with_overrides(self) -> Self {
+        self.with_env().with_args(std::env::args().skip(1))
+    }
+
+    /// Applies the `WGPU_*` environment variables listed on [`RendererConfig`].
+    pub fn with_env(self) -> Self {
+        self.with_vars(|key| std::env::var(key).ok())
+    }
+
+    /// Applies the variables listed on [`RendererConfig`], looked up with `var`.
+    fn with_vars(mut self, var: impl Fn(&str) -> Option<String>) -> Self {
+        for (key, option) in ENV_VARS {
+            if let Some(value) = var(key) {
+                self.apply(option, &value, &format!("{key}={value}"));
+            }
+        }
+        self
+    }
+
+    /// Applies the arguments listed on [`RendererConfig`], as `--name value` or `--name=value`.
+    /// Arguments that are not renderer options are ignored.
+    pub fn with_args<I, S>(mut self, args: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            let arg = arg.as_ref();
+            let (name, inline_value) = match arg.split_once('=') {
+                Some((name, value)) => (name, Some(value.to_string())),
+                None => (arg, None),
+            };
+            let Some(&(_, option)) = ARGS.iter().find(|(flag, _)| *flag == name) else {
+                continue;
+            };
+
+            let value = if matches!(
+                option,
+                RendererOption::FallbackAdapter | RendererOption::HotReload
+            ) {
+                inline_value.unwrap_or_else(|| "true".to_string())
+            } else if let Some(value) =
+                inline_value.or_else(|| args.next().map(|v| v.as_ref().to_string()))
+            {
+                value
+            } else {
+                warn!("{name} expects a value, ignoring it");
+                continue;
+            };
+            self.apply(option, &value, &format!("{name} {value}"));
+        }
+        self
+    }
+
+    fn apply(&mut self, option: RendererOption, value: &str, source: &str) {
+        let raw_value = value.trim();
+        let value = raw_value.to_lowercase();
+        match option {
+            RendererOption::Backend => match parse_backends(&value) {
+                Some(backends) => {
+                    info!("{source}: backends {:?} -> {backends:?}", self.backends);
+                    self.backends = backends;
+                }
+                None => warn!("{source}: unknown backend, keeping {:?}", self.backends),
+            },
+            RendererOption::PowerPreference => match parse_power_preference(&value) {
+                Some(power_preference) => {
+                    info!(
+                        "{source}: power preference {:?} -> {power_preference:?}",
+                        self.power_preference
+                    );
+                    self.power_preference = power_preference;
+                }
+                None => warn!(
+                    "{source}: unknown power preference, keeping {:?}",
+                    self.power_preference
+                ),
+            },
+            RendererOption::FallbackAdapter => match parse_bool(&value) {
+                Some(force_fallback_adapter) => {
+                    info!("{source}: force fallback adapter = {force_fallback_adapter}");
+                    self.force_fallback_adapter = force_fallback_adapter;
+                }
+                None => warn!(
+                    "{source}: expected true or false, keeping {}",
+                    self.force_fallback_adapter
+                ),
+            },
+            RendererOption::AdapterName => {
+                info!("{source}: selecting adapters whose name contains {value:?}");
+                self.adapter.name = Some(value);
+            }
+            RendererOption::DeviceType => match parse_device_type(&value) {
+                Some(device_type) => {
+                    info!("{source}: selecting {device_type:?} adapters");
+                    self.adapter.device_type = Some(device_type);
+                }
+                None => warn!(
+                    "{source}: unknown device type, keeping {:?}",
+                    self.adapter.device_type
+                ),
+            },
+            RendererOption::Limits => match parse_limits(&value) {
+                Some(limits) => {
+                    info!("{source}: using {value} limits");
+                    self.required_limits = limits;
+                }
+                None => warn!("{source}: unknown limits, keeping the current ones"),
+            },
+            RendererOption::PresentMode => match parse_present_mode(&value) {
+                Some(present_mode) => {
+                    info!("{source}: present mode {present_mode:?} requested");
+                    self.present_mode = Some(present_mode);
+                }
+                None => warn!(
+                    "{source}: unknown present mode, keeping {:?}",
+                    self.present_mode
+                ),
+            },
+            RendererOption::FrameLatency => match value.parse::<u32>() {
+                Ok(latency) if latency > 0 => {
+                    info!(
+                        "{source}: frame latency {} -> {latency}",
+                        self.desired_maximum_frame_latency
+                    );
+                    self.desired_maximum_frame_latency = latency;
+                }
+                _ => warn!(
+                    "{source}: expected a positive integer, keeping {}",
+                    self.desired_maximum_frame_latency
+                ),
+            },
+            RendererOption::GpuTrace => {
+                // Paths are case-sensitive, unlike the other values.
+                info!("{source}: writing a GPU trace to {raw_value:?}");
+                self.gpu_profiling = true;
+                self.gpu_trace = Some(PathBuf::from(raw_value));
+            }
+            RendererOption::Record => {
+                info!("{source}: recording to {raw_value:?}");
+                let recording = self.record.get_or_insert_with(Recording::default);
+                *recording = Recording::new(raw_value)
+                    .frames(recording.frames)
+                    .fps(recording.fps);
+            }
+            RendererOption::RecordFrames => match value.parse::<u32>() {
+                Ok(frames) if frames > 0 => {
+                    info!("{source}: recording {frames} frames");
+                    self.record.get_or_insert_with(Recording::default).frames = frames;
+                }
+                _ => warn!("{source}: expected a positive integer, ignoring it"),
+            },
+            RendererOption::RecordFps => match value.parse::<f64>() {
+                Ok(fps) if fps > 0.0 => {
+                    info!("{source}: recording at {fps} frames per second");
+                    self.record.get_or_insert_with(Recording::default).fps = fps;
+                }
+                _ => warn!("{source}: expected a positive frame rate, ignoring it"),
+            },
+            RendererOption::ScreenshotDir => {
+                info!("{source}: saving screenshots in {raw_value:?}");
+                self.screenshot_dir = PathBuf::from(raw_value);
+            }
+            RendererOption::Bindings => {
+                info!("{source}: reading key bindings from {raw_value:?}");
+                self.bindings = Some(PathBuf::from(raw_value));
+            }
+            RendererOption::RecordInput => {
+                info!("{source}: recording the input to {raw_value:?}");
+                self.record_input = Some(PathBuf::from(raw_value));
+            }
+            RendererOption::ReplayInput => {
+                info!("{source}: replaying the input in {raw_value:?}");
+                self.replay_input = Some(PathBuf::from(raw_value));
+            }
+            RendererOption::HotReload => match parse_bool(&value) {
+                Some(hot_reload) => {
+                    info!("{source}: hot reload = {hot_reload}");
+                    self.hot_reload = hot_reload;
+                }
+                None => warn!(
+                    "{source}: expected true or false, keeping {}",
+                    self.hot_reload
+                ),
+            },
+        }
+    }
+
+    /// Requests an adapter matching this configuration. When none is found and a fallback
+    /// adapter was not already forced, retries with one. An adapter selector that matches
+    /// nothing gives no adapter.
+    pub(crate) async fn request_adapter(
+        &self,
+        instance: &wgpu::Instance,
+        compatible_surface: Option<&wgpu::Surface<'_>>,
+    ) -> Option<wgpu::Adapter> {
+        let request = |force_fallback_adapter| {
+            instance.request_adapter(&wgpu::RequestAdapterOptions {
+                power_preference: self.power_preference,
+                compatible_surface,
+                force_fallback_adapter,
+            })
+        };
+
+        if !self.adapter.is_empty() {
+            let selected = instance
+                .enumerate_adapters(self.backends)
+                .into_iter()
+                .find(|adapter| {
+                    self.adapter.matches(&adapter.get_info())
+                        && compatible_surface
+                            .is_none_or(|surface| adapter.is_surface_supported(surface))
+                });
+            match selected {
+                Some(adapter) => {
+                    let adapter_info = adapter.get_info();
+                    info!(
+                        "Using adapter {:?} ({:?}, {:?}), it matches {}",
+                        adapter_info.name,
+                        adapter_info.backend,
+                        adapter_info.device_type,
+                        self.adapter
+                    );
+                    return Some(adapter);
+                }
+                None => {
+                    warn!("No {:?} adapter matches {}", self.backends, self.adapter);
+                    return None;
+                }
+            }
+        }
+
+        let adapter = match request(self.force_fallback_adapter).await {
+            Some(adapter) => adapter,
+            None if self.force_fallback_adapter => {
+                warn!("No fallback adapter available for {:?}", self.backends);
+                return None;
+            }
+            None => {
+                warn!(
+                    "No {:?} adapter available for {:?}, falling back to a software adapter",
+                    self.power_preference, self.backends
+                );
+                request(true).await?
+            }
+        };
+
+        let adapter_info = adapter.get_info();
+        info!(
+            "Using adapter {:?} ({:?}, {:?})",
+            adapter_info.name, adapter_info.backend, adapter_info.device_type
+        );
+        Some(adapter)
+    }
+
+    /// Checks the required features and limits against `adapter`, and returns them together with
+    /// the optional ones it supports.
+    pub(crate) fn negotiate(
+        &self,
+        adapter: &wgpu::Adapter,
+    ) -> Result<(wgpu::Features, wgpu::Limits)> {
+        self.negotiate_with(
+            adapter.get_info().name,
+            adapter.features(),
+            adapter.limits(),
+        )
+    }
+
+    /// [`negotiate`](Self::negotiate) against what an adapter named `adapter_name` supports.
+    fn negotiate_with(
+        &self,
+        adapter_name: String,
+        adapter_features: wgpu::Features,
+        adapter_limits: wgpu::Limits,
+    ) -> Result<(wgpu::Features, wgpu::Limits)> {
+        let missing_features = self.required_features - adapter_features;
+        if !missing_features.is_empty() {
+            return Err(Error::MissingFeatures {
+                adapter: adapter_name,
+                features: missing_features,
+            });
+        }
+
+        let mut missing_limits = Vec::new();
+        self.required_limits.check_limits_with_fail_fn(
+            &adapter_limits,
+            false,
+            |name, requested, supported| {
+                missing_limits.push(format!(
+                    "{name} (requested {requested}, supported {supported})"
+                ))
+            },
+        );
+        if !missing_limits.is_empty() {
+            return Err(Error::MissingLimits {
+                adapter: adapter_name,
+                limits: missing_limits,
+            });
+        }
+
+        let mut optional_features = self.optional_features;
+        if self.gpu_profiling {
+            optional_features |=
+                wgpu::Features::TIMESTAMP_QUERY | wgpu::Features::TIMESTAMP_QUERY_INSIDE_ENCODERS;
+        }
+        let granted_features = optional_features & adapter_features;
+        let denied_features = optional_features - granted_features;
+        if !granted_features.is_empty() {
+            info!("Optional features enabled: {granted_features:?}");
+        }
+        if !denied_features.is_empty() {
+            warn!("Optional features not supported by {adapter_name:?}: {denied_features:?}");
+        }
+
+        let limits = match &self.optional_limits {
+            Some(optional_limits) => {
+                let mut unsupported = Vec::new();
+                optional_limits.check_limits_with_fail_fn(
+                    &adapter_limits,
+                    false,
+                    |name, requested, supported| {
+                        unsupported.push(format!(
+                            "{name} (requested {requested}, supported {supported})"
+                        ))
+                    },
+                );
+                if unsupported.is_empty() {
+                    info!("Using the optional limits");
+                    optional_limits.clone()
+                } else {
+                    warn!(
+                        "Adapter {adapter_name:?} does not support the optional limits, using the required ones: {}",
+                        unsupported.join(", ")
+                    );
+                    self.required_limits.clone()
+                }
+            }
+            None => self.required_limits.clone(),
+        };
+
+        Ok((self.required_features | granted_features, limits))
+    }
+
+    /// Picks the requested present mode if the surface supports it, the first supported one
+    /// otherwise.
+    pub(crate) fn choose_present_mode(&self, supported: &[wgpu::PresentMode]) -> wgpu::PresentMode {
+        let first = supported[0];
+        match self.present_mode {
+            Some(present_mode) if supported.contains(&present_mode) => {
+                info!("Using present mode {present_mode:?} as requested");
+                present_mode
+            }
+            // The automatic modes are resolved by wgpu itself, so they are always accepted.
+            Some(
+                present_mode @ (wgpu::PresentMode::AutoVsync | wgpu::PresentMode::AutoNoVsync),
+            ) => {
+                info!("Using present mode {present_mode:?} as requested");
+                present_mode
+            }
+            Some(present_mode) => {
+                warn!(
+                    "Present mode {present_mode:?} is not supported by the surface ({supported:?}), falling back to {first:?}"
+                );
+                first
+            }
+            None => {
+                info!("Using present mode {first:?}, the surface's preferred mode");
+                first
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum RendererOption {
+    Backend,
+    PowerPreference,
+    FallbackAdapter,
+    AdapterName,
+    DeviceType,
+    Limits,
+    PresentMode,
+    FrameLatency,
+    GpuTrace,
+    ScreenshotDir,
+    Record,
+    RecordFrames,
+    RecordFps,
+    Bindings,
+    RecordInput,
+    ReplayInput,
+    HotReload,
+}
+
+const ENV_VARS: [(&str, RendererOption); 17] = [
+    ("WGPU_BACKEND", RendererOption::Backend),
+    ("WGPU_POWER_PREF", RendererOption::PowerPreference),
+    (
+        "WGPU_FORCE_FALLBACK_ADAPTER",
+        RendererOption::FallbackAdapter,
+    ),
+    ("WGPU_ADAPTER_NAME", RendererOption::AdapterName),
+    ("WGPU_DEVICE_TYPE", RendererOption::DeviceType),
+    ("WGPU_LIMITS", RendererOption::Limits),
+    ("WGPU_PRESENT_MODE", RendererOption::PresentMode),
+    ("WGPU_FRAME_LATENCY", RendererOption::FrameLatency),
+    ("WGPU_GPU_TRACE", RendererOption::GpuTrace),
+    ("WGPU_SCREENSHOT_DIR", RendererOption::ScreenshotDir),
+    ("WGPU_RECORD", RendererOption::Record),
+    ("WGPU_RECORD_FRAMES", RendererOption::RecordFrames),
+    ("WGPU_RECORD_FPS", RendererOption::RecordFps),
+    ("WGPU_BINDINGS", RendererOption::Bindings),
+    ("WGPU_RECORD_INPUT", RendererOption::RecordInput),
+    ("WGPU_REPLAY_INPUT", RendererOption::ReplayInput),
+    ("WGPU_HOT_RELOAD", RendererOption::HotReload),
+];
+
+const ARGS: [(&str, RendererOption); 17] = [
+    ("--backend", RendererOption::Backend),
+    ("--power", RendererOption::PowerPreference),
+    ("--fallback-adapter", RendererOption::FallbackAdapter),
+    ("--adapter", RendererOption::AdapterName),
+    ("--device-type", RendererOption::DeviceType),
+    ("--limits", RendererOption::Limits),
+    ("--present-mode", RendererOption::PresentMode),
+    ("--frame-latency", RendererOption::FrameLatency),
+    ("--gpu-trace", RendererOption::GpuTrace),
+    ("--screenshot-dir", RendererOption::ScreenshotDir),
+    ("--record", RendererOption::Record),
+    ("--record-frames", RendererOption::RecordFrames),
+    ("--record-fps", RendererOption::RecordFps),
+    ("--bindings", RendererOption::Bindings),
+    ("--record-input", RendererOption::RecordInput),
+    ("--replay-input", RendererOption::ReplayInput),
+    ("--hot-reload", RendererOption::HotReload),
+];
+
+fn parse_backends(value: &str) -> Option<wgpu::Backends> {
+    value
+        .split(',')
+        .try_fold(wgpu::Backends::empty(), |backends, name| {
+            let backend = match name.trim() {
+                "vulkan" | "vk" => wgpu::Backends::VULKAN,
+                "metal" | "mtl" => wgpu::Backends::METAL,
+                "dx12" | "d3d12" => wgpu::Backends::DX12,
+                "gl" | "gles" | "opengl" => wgpu::Backends::GL,
+                "webgpu" | "browser" => wgpu::Backends::BROWSER_WEBGPU,
+                "primary" => wgpu::Backends::PRIMARY,
+                "secondary" => wgpu::Backends::SECONDARY,
+                "all" => wgpu::Backends::all(),
+                _ => return None,
+            };
+            Some(backends | backend)
+        })
+}
+
+fn parse_power_preference(value: &str) -> Option<wgpu::PowerPreference> {
+    match value {
+        "low" => Some(wgpu::PowerPreference::LowPower),
+        "high" => Some(wgpu::PowerPreference::HighPerformance),
+        "none" => Some(wgpu::PowerPreference::None),
+        _ => None,
+    }
+}
+
+fn parse_bool(value: &str) -> Option<bool> {
+    match value {
+        "1" | "true" | "yes" | "on" => Some(true),
+        "0" | "false" | "no" | "off" => Some(false),
+        _ => None,
+    }
+}
+
+fn parse_device_type(value: &str) -> Option<wgpu::DeviceType> {
+    match value {
+        "discrete" => Some(wgpu::DeviceType::DiscreteGpu),
+        "integrated" => Some(wgpu::DeviceType::IntegratedGpu),
+        "virtual" => Some(wgpu::DeviceType::VirtualGpu),
+        "cpu" => Some(wgpu::DeviceType::Cpu),
+        "other" => Some(wgpu::DeviceType::Other),
+        _ => None,
+    }
+}
+
+fn parse_limits(value: &str) -> Option<wgpu::Limits> {
+    match value {
+        "default" => Some(wgpu::Limits::default()),
+        "downlevel" => Some(wgpu::Limits::downlevel_defaults()),
+        "webgl2" => Some(wgpu::Limits::downlevel_webgl2_defaults()),
+        _ => None,
+    }
+}
+
+fn parse_present_mode(value: &str) -> Option<wgpu::PresentMode> {
+    match value {
+        "auto-vsync" => Some(wgpu::PresentMode::AutoVsync),
+        "auto-no-vsync" => Some(wgpu::PresentMode::AutoNoVsync),
+        "fifo" => Some(wgpu::PresentMode::Fifo),
+        "fifo-relaxed" => Some(wgpu::PresentMode::FifoRelaxed),
+        "mailbox" => Some(wgpu::PresentMode::Mailbox),
+        "immediate" => Some(wgpu::PresentMode::Immediate),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::RecordFormat;
+    use std::path::Path;
+
+    /// Negotiates with an adapter that has two features and the downlevel limits.
+    fn negotiate(config: &RendererConfig) -> Result<(wgpu::Features, wgpu::Limits)> {
+        config.negotiate_with(
+            "test adapter".to_string(),
+            wgpu::Features::DEPTH_CLIP_CONTROL | wgpu::Features::POLYGON_MODE_LINE,
+            wgpu::Limits::downlevel_defaults(),
+        )
+    }
+
+    #[test]
+    fn unsupported_optional_features_are_dropped() {
+        let config = RendererConfig::default()
+            .required_features(wgpu::Features::DEPTH_CLIP_CONTROL)
+            .optional_features(wgpu::Features::POLYGON_MODE_LINE | wgpu::Features::SHADER_F64)
+            .required_limits(wgpu::Limits::downlevel_defaults());
+        let (features, _) = negotiate(&config).unwrap();
+        assert_eq!(
+            features,
+            wgpu::Features::DEPTH_CLIP_CONTROL | wgpu::Features::POLYGON_MODE_LINE
+        );
+
+        // Profiling asks for timestamp queries, but runs without them.
+        let (features, _) = negotiate(&config.gpu_profiling(true)).unwrap();
+        assert!(!features.contains(wgpu::Features::TIMESTAMP_QUERY));
+    }
+
+    #[test]
+    fn unsupported_required_features_are_an_error() {
+        let config = RendererConfig::default()
+            .required_features(wgpu::Features::SHADER_F64 | wgpu::Features::POLYGON_MODE_LINE)
+            .required_limits(wgpu::Limits::downlevel_defaults());
+        match negotiate(&config) {
+            Err(Error::MissingFeatures { adapter, features }) => {
+                assert_eq!(adapter, "test adapter");
+                assert_eq!(features, wgpu::Features::SHADER_F64);
+            }
+            other => panic!("expected missing features, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn unsupported_optional_limits_fall_back_to_the_required_ones() {
+        let required = wgpu::Limits::downlevel_webgl2_defaults();
+        let config = RendererConfig::default()
+            .required_limits(required.clone())
+            .optional_limits(wgpu::Limits::default());
+        let (_, limits) = negotiate(&config).unwrap();
+        assert_eq!(limits, required);
+
+        let config = config.optional_limits(wgpu::Limits::downlevel_defaults());
+        let (_, limits) = negotiate(&config).unwrap();
+        assert_eq!(limits, wgpu::Limits::downlevel_defaults());
+    }
+
+    #[test]
+    fn unsupported_required_limits_are_an_error() {
+        let config = RendererConfig::default().required_limits(wgpu::Limits::default());
+        match negotiate(&config) {
+            Err(Error::MissingLimits { adapter, limits }) => {
+                assert_eq!(adapter, "test adapter");
+                assert!(
+                    limits
+                        .iter()
+                        .any(|limit| limit.starts_with("max_texture_dimension_2d")),
+                    "{limits:?}"
+                );
+            }
+            other => panic!("expected missing limits, got {other:?}"),
+        }
+    }
+
+    type IsSet = fn(&RendererConfig) -> bool;
+
+    /// A valid value of every option, and what it must have set.
+    const OPTIONS: [(RendererOption, &str, IsSet); 17] = [
+        (RendererOption::Backend, "Vulkan, gl", |c| {
+            c.backends == wgpu::Backends::VULKAN | wgpu::Backends::GL
+        }),
+        (RendererOption::PowerPreference, "high", |c| {
+            c.power_preference == wgpu::PowerPreference::HighPerformance
+        }),
+        (RendererOption::FallbackAdapter, "yes", |c| {
+            c.force_fallback_adapter
+        }),
+        (RendererOption::AdapterName, "LLVMpipe", |c| {
+            c.adapter.name.as_deref() == Some("llvmpipe")
+        }),
+        (RendererOption::DeviceType, "cpu", |c| {
+            c.adapter.device_type == Some(wgpu::DeviceType::Cpu)
+        }),
+        (RendererOption::Limits, "webgl2", |c| {
+            c.required_limits == wgpu::Limits::downlevel_webgl2_defaults()
+        }),
+        (RendererOption::PresentMode, "mailbox", |c| {
+            c.present_mode == Some(wgpu::PresentMode::Mailbox)
+        }),
+        (RendererOption::FrameLatency, "3", |c| {
+            c.desired_maximum_frame_latency == 3
+        }),
+        (RendererOption::GpuTrace, "Trace.json", |c| {
+            c.gpu_profiling && c.gpu_trace == Some(PathBuf::from("Trace.json"))
+        }),
+        (RendererOption::ScreenshotDir, "Shots", |c| {
+            c.screenshot_dir == Path::new("Shots")
+        }),
+        (RendererOption::Record, "Out.png", |c| {
+            c.record.as_ref().map(|r| (r.path(), r.format))
+                == Some((Path::new("Out.png"), RecordFormat::Apng))
+        }),
+        (RendererOption::RecordFrames, "12", |c| {
+            c.record.as_ref().map(Recording::frame_count) == Some(12)
+        }),
+        (RendererOption::RecordFps, "24", |c| {
+            c.record.as_ref().map(|r| r.fps) == Some(24.0)
+        }),
+        (RendererOption::Bindings, "Keys.toml", |c| {
+            c.bindings == Some(PathBuf::from("Keys.toml"))
+        }),
+        (RendererOption::RecordInput, "In.ron", |c| {
+            c.record_input == Some(PathBuf::from("In.ron"))
+        }),
+        (RendererOption::ReplayInput, "In.ron", |c| {
+            c.replay_input == Some(PathBuf::from("In.ron"))
+        }),
+        (RendererOption::HotReload, "on", |c| c.hot_reload),
+    ];
+
+    fn variable(option: RendererOption) -> &'static str {
+        ENV_VARS.iter().find(|(_, o)| *o == option).unwrap().0
+    }
+
+    fn argument(option: RendererOption) -> &'static str {
+        ARGS.iter().find(|(_, o)| *o == option).unwrap().0
+    }
+
+    fn with_vars(vars: &[(&str, &str)]) -> RendererConfig {
+        RendererConfig::default().with_vars(|key| {
+            vars.iter()
+                .find(|(k, _)| *k == key)
+                .map(|(_, value)| value.to_string())
+        })
+    }
+
+    /// The configuration as text, to compare whole configurations.
+    fn dump(config: &RendererConfig) -> String {
+        format!("{config:?}")
+    }
+
+    #[test]
+    fn every_variable_sets_its_option() {
+        for (option, value, is_set) in OPTIONS {
+            let config = with_vars(&[(variable(option), value)]);
+            assert!(is_set(&config), "{}={value}: {config:?}", variable(option));
+        }
+    }
+
+    #[test]
+    fn every_argument_sets_its_option() {
+        for (option, value, is_set) in OPTIONS {
+            let name = argument(option);
+            let config = RendererConfig::default().with_args([name, value]);
+            assert!(is_set(&config), "{name} {value}: {config:?}");
+            let config = RendererConfig::default().with_args([format!("{name}={value}")]);
+            assert!(is_set(&config), "{name}={value}: {config:?}");
+        }
+
+        // Flags don't need a value.
+        let config = RendererConfig::default().with_args(["--fallback-adapter", "--hot-reload"]);
+        assert!(config.force_fallback_adapter && config.hot_reload);
+        let config = RendererConfig::default()
+            .hot_reload(true)
+            .with_args(["--hot-reload=false"]);
+        assert!(!config.hot_reload);
+    }
+
+    #[test]
+    fn bad_values_keep_the_current_ones() {
+        let bad = [
+            (RendererOption::Backend, "vulkan,glide"),
+            (RendererOption::PowerPreference, "max"),
+            (RendererOption::FallbackAdapter, "maybe"),
+            (RendererOption::DeviceType, "gpu"),
+            (RendererOption::Limits, "huge"),
+            (RendererOption::PresentMode, "vsync"),
+            (RendererOption::FrameLatency, "0"),
+            (RendererOption::FrameLatency, "-1"),
+            (RendererOption::RecordFrames, "0"),
+            (RendererOption::RecordFrames, "many"),
+            (RendererOption::RecordFps, "0"),
+            (RendererOption::RecordFps, "fast"),
+            (RendererOption::HotReload, "maybe"),
+        ];
+        let default = dump(&RendererConfig::default());
+        for (option, value) in bad {
+            let name = argument(option);
+            // Flags take their value after `=`.
+            let config = RendererConfig::default().with_args([format!("{name}={value}")]);
+            assert_eq!(dump(&config), default, "{name}={value}");
+            let config = with_vars(&[(variable(option), value)]);
+            assert_eq!(dump(&config), default, "{}={value}", variable(option));
+        }
+
+        // Other arguments are left to the app, and a missing value is ignored.
+        let config =
+            RendererConfig::default().with_args(["--list-adapters", "scene.obj", "--power"]);
+        assert_eq!(dump(&config), default);
+    }
+
+    #[test]
+    fn arguments_take_precedence_over_variables() {
+        let config = with_vars(&[("WGPU_BACKEND", "gl"), ("WGPU_FRAME_LATENCY", "3")])
+            .with_args(["--backend", "vulkan"]);
+        assert_eq!(config.backends, wgpu::Backends::VULKAN);
+        assert_eq!(config.desired_maximum_frame_latency, 3);
+
+        // The recording options combine in any order.
+        let config = with_vars(&[("WGPU_RECORD_FRAMES", "12")]).with_args([
+            "--record-fps",
+            "24",
+            "--record",
+            "out.gif",
+        ]);
+        let recording = config.record.unwrap();
+        assert_eq!(recording.path(), Path::new("out.gif"));
+        assert_eq!((recording.frame_count(), recording.fps), (12, 24.0));
+    }
+}
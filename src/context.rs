@@ -0,0 +1,335 @@
+use crate::registry::{
+    BindGroupLayoutRecipe, BindGroupRecipe, BufferRecipe, PipelineLayoutRecipe, Recipe, Registry,
+    RenderPipelineRecipe, ShaderModuleRecipe, TextureRecipe,
+};
+use crate::texture::{Texture, TextureOptions};
+use crate::{Error, InputState, MemoryTracker, RendererConfig, Restorable, Result, TrackedBuffer};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use winit::dpi::PhysicalSize;
+use winit::window::{WindowAttributes, WindowId};
+
+/// The GPU objects an [`Application`](crate::Application) renders with, together with the format
+/// and size of the color target it draws into.
+///
+/// All windows share the device and queue. `size` and `window` describe the window the current
+/// hook was called for.
+///
+/// The pipelines, buffers, textures and bind groups created with the context's `create_*`
+/// methods are [`Restorable`]: the context keeps their descriptors and contents, and
+/// [`State::recover`](crate::State::recover) creates them again on the new device after a device
+/// loss. Resources created directly on `device` are not.
+pub struct GpuContext {
+    pub instance: wgpu::Instance,
+    pub adapter: wgpu::Adapter,
+    pub device: wgpu::Device,
+    pub queue: wgpu::Queue,
+    pub format: wgpu::TextureFormat,
+    pub size: PhysicalSize<u32>,
+    /// The window being handled, `None` when headless.
+    pub window: Option<WindowId>,
+    /// Accounts for the buffers and textures created through the context or tracked with it.
+    pub memory: MemoryTracker,
+    /// The keyboard and mouse, kept up to date by the [`State`](crate::State) from the events
+    /// of all windows.
+    pub input: InputState,
+    /// Whether shaders follow their files on disk, see [`HotShader`](crate::HotShader).
+    pub hot_reload: bool,
+    redraw_requested: AtomicBool,
+    /// Set by the file watchers of hot-reloaded shaders.
+    pub(crate) shaders_changed: Arc<AtomicBool>,
+    window_requests: Mutex<Vec<WindowRequest>>,
+    /// Set by the device-lost callback.
+    device_lost: Arc<AtomicBool>,
+    /// What the restorable resources were created from.
+    pub(crate) resources: Registry,
+    /// The first error nothing captured since the last frame, set by the uncaptured-error
+    /// handler.
+    uncaptured_error: Arc<Mutex<Option<String>>>,
+}
+
+/// A change to the set of windows, carried out by the runner once the current event is handled.
+pub(crate) enum WindowRequest {
+    Open(Box<WindowAttributes>),
+    Close(WindowId),
+}
+
+impl GpuContext {
+    pub async fn new(
+        instance: wgpu::Instance,
+        adapter: wgpu::Adapter,
+        format: wgpu::TextureFormat,
+        size: PhysicalSize<u32>,
+        config: &RendererConfig,
+    ) -> Result<Self> {
+        let (required_features, required_limits) = config.negotiate(&adapter)?;
+
+        let (device, queue) = adapter
+            .request_device(
+                &wgpu::DeviceDescriptor {
+                    required_features,
+                    required_limits,
+                    label: None,
+                },
+                None,
+            )
+            .await?;
+
+        // wgpu panics on errors nothing captured; keep them for the frame to fail with instead,
+        // a lost device is recovered from by `State::recover`.
+        let uncaptured_error = Arc::new(Mutex::new(None));
+        let first_error = Arc::clone(&uncaptured_error);
+        device.on_uncaptured_error(Box::new(move |error| {
+            log::error!("Uncaptured wgpu error: {error}");
+            first_error
+                .lock()
+                .unwrap()
+                .get_or_insert_with(|| error.to_string());
+        }));
+        let device_lost = Arc::new(AtomicBool::new(false));
+        let lost = Arc::clone(&device_lost);
+        device.set_device_lost_callback(move |reason, message| match reason {
+            // The device was dropped or the callback replaced on purpose.
+            wgpu::DeviceLostReason::Dropped | wgpu::DeviceLostReason::ReplacedCallback => {}
+            _ => {
+                log::error!("Device lost ({reason:?}): {message}");
+                lost.store(true, Ordering::Relaxed);
+            }
+        });
+
+        Ok(Self {
+            instance,
+            adapter,
+            device,
+            queue,
+            format,
+            size,
+            window: None,
+            memory: MemoryTracker::new(),
+            input: InputState::new(),
+            hot_reload: config.hot_reload,
+            redraw_requested: AtomicBool::new(false),
+            shaders_changed: Arc::new(AtomicBool::new(false)),
+            window_requests: Mutex::default(),
+            device_lost,
+            resources: Registry::default(),
+            uncaptured_error,
+        })
+    }
+
+    /// Creates a shader module, reporting parse and validation errors instead of panicking.
+    /// Errors in WGSL are reported with the line and column they are at. Only WGSL modules are
+    /// restorable.
+    pub fn try_create_shader_module(
+        &self,
+        desc: wgpu::ShaderModuleDescriptor,
+    ) -> Result<Restorable<wgpu::ShaderModule>> {
+        let label = desc.label.unwrap_or("unnamed").to_string();
+        if let wgpu::ShaderSource::Wgsl(source) = &desc.source {
+            crate::shader::check_wgsl(&label, source)?;
+        }
+        let recipe = ShaderModuleRecipe::new(&desc)?;
+
+        self.device.push_error_scope(wgpu::ErrorFilter::Validation);
+        let module = recipe.create(self);
+        match pollster::block_on(self.device.pop_error_scope()) {
+            None => Ok(self.resources.register(module, recipe)),
+            Some(e) => Err(Error::Shader {
+                label,
+                message: e.to_string(),
+            }),
+        }
+    }
+
+    /// Creates a render pipeline, reporting validation errors instead of panicking, for example
+    /// when the entry points of a reloaded shader no longer match the pipeline. Its layout and
+    /// shader modules must have been created through the context.
+    pub fn try_create_render_pipeline(
+        &self,
+        desc: &wgpu::RenderPipelineDescriptor,
+    ) -> Result<Restorable<wgpu::RenderPipeline>> {
+        let label = desc.label.unwrap_or("unnamed").to_string();
+        let recipe = RenderPipelineRecipe::new(&self.resources, desc)?;
+
+        self.device.push_error_scope(wgpu::ErrorFilter::Validation);
+        let pipeline = recipe.create(self);
+        match pollster::block_on(self.device.pop_error_scope()) {
+            None => Ok(self.resources.register(pipeline, recipe)),
+            Some(e) => Err(Error::Pipeline {
+                label,
+                message: e.to_string(),
+            }),
+        }
+    }
+
+    /// Creates a buffer filled with `desc.contents`, recorded in [`GpuContext::memory`] until it
+    /// is dropped. After a device loss it is filled with `desc.contents` again, not with what was
+    /// written into it since.
+    pub fn create_buffer_init(
+        &self,
+        desc: &wgpu::util::BufferInitDescriptor,
+    ) -> Restorable<TrackedBuffer> {
+        self.resources.create(self, BufferRecipe::new(desc))
+    }
+
+    /// Creates an uninitialized [`Texture`], recorded in [`GpuContext::memory`] until it is
+    /// dropped.
+    pub fn create_texture(
+        &self,
+        width: u32,
+        height: u32,
+        options: &TextureOptions,
+    ) -> Restorable<Texture> {
+        let recipe = TextureRecipe::new(width, height, options, None);
+        self.resources.create(self, recipe)
+    }
+
+    /// Uploads `img` into a [`Texture`] like [`Texture::from_image_with`], recorded in
+    /// [`GpuContext::memory`] until it is dropped.
+    pub fn create_texture_from_image(
+        &self,
+        img: &image::DynamicImage,
+        options: &TextureOptions,
+    ) -> Result<Restorable<Texture>> {
+        let options = Texture::upload_options(options)?;
+        let rgba = img.to_rgba8();
+        let recipe =
+            TextureRecipe::new(rgba.width(), rgba.height(), &options, Some(rgba.into_raw()));
+        Ok(self.resources.create(self, recipe))
+    }
+
+    /// Decodes an encoded image (PNG, JPEG, ...) into a sampled sRGB [`Texture`] like
+    /// [`Texture::from_bytes`], recorded in [`GpuContext::memory`] until it is dropped.
+    pub fn create_texture_from_bytes(
+        &self,
+        bytes: &[u8],
+        label: &str,
+    ) -> Result<Restorable<Texture>> {
+        let img = image::load_from_memory(bytes).map_err(|e| Error::asset(label, e))?;
+        let options = TextureOptions {
+            label: Some(label),
+            ..Default::default()
+        };
+        self.create_texture_from_image(&img, &options)
+    }
+
+    /// Creates a bind group layout, which the bind groups and pipeline layouts created through the
+    /// context can use.
+    pub fn create_bind_group_layout(
+        &self,
+        desc: &wgpu::BindGroupLayoutDescriptor,
+    ) -> Restorable<wgpu::BindGroupLayout> {
+        self.resources
+            .create(self, BindGroupLayoutRecipe::new(desc))
+    }
+
+    /// Creates a pipeline layout from bind group layouts created through the context.
+    pub fn create_pipeline_layout(
+        &self,
+        desc: &wgpu::PipelineLayoutDescriptor,
+    ) -> Result<Restorable<wgpu::PipelineLayout>> {
+        let recipe = PipelineLayoutRecipe::new(&self.resources, desc)?;
+        Ok(self.resources.create(self, recipe))
+    }
+
+    /// Creates a bind group whose layout, buffers and textures were created through the context.
+    /// Texture views and samplers are those of a [`Texture`]; binding arrays are not supported.
+    pub fn create_bind_group(
+        &self,
+        desc: &wgpu::BindGroupDescriptor,
+    ) -> Result<Restorable<wgpu::BindGroup>> {
+        let recipe = BindGroupRecipe::new(&self.resources, desc)?;
+        Ok(self.resources.create(self, recipe))
+    }
+
+    /// Marks the app as changed, so a new frame is rendered even with
+    /// [`RedrawPolicy::OnDemand`](crate::RedrawPolicy::OnDemand).
+    pub fn request_redraw(&self) {
+        self.redraw_requested.store(true, Ordering::Relaxed);
+    }
+
+    /// Returns whether a redraw was requested since the last call, and clears the request.
+    pub(crate) fn take_redraw_request(&self) -> bool {
+        self.redraw_requested.swap(false, Ordering::Relaxed)
+    }
+
+    /// The flag the file watchers of hot-reloaded shaders set, from their own thread.
+    pub(crate) fn shader_change_flag(&self) -> Arc<AtomicBool> {
+        Arc::clone(&self.shaders_changed)
+    }
+
+    /// Returns whether a watched shader file changed since the last call, and clears the flag.
+    pub(crate) fn take_shader_change(&self) -> bool {
+        self.shaders_changed.swap(false, Ordering::Relaxed)
+    }
+
+    /// Opens another window that renders with this context. Once it is ready,
+    /// [`Application::window_opened`](crate::Application::window_opened) is called with its id.
+    /// Windows are only opened by the runner; headless states ignore the request.
+    pub fn open_window(&self, attributes: WindowAttributes) {
+        let mut requests = self.window_requests.lock().unwrap();
+        requests.push(WindowRequest::Open(Box::new(attributes)));
+    }
+
+    /// Closes a window as if the user had closed it. The runner exits once no window is left.
+    pub fn close_window(&self, window: WindowId) {
+        let mut requests = self.window_requests.lock().unwrap();
+        requests.push(WindowRequest::Close(window));
+    }
+
+    pub(crate) fn take_window_requests(&self) -> Vec<WindowRequest> {
+        std::mem::take(&mut *self.window_requests.lock().unwrap())
+    }
+
+    /// Whether the device was lost, e.g. after a driver reset. Nothing can be rendered with it
+    /// any more until [`State::recover`](crate::State::recover) replaced it.
+    pub fn is_device_lost(&self) -> bool {
+        self.device_lost.load(Ordering::Relaxed)
+    }
+
+    /// Returns the first error wgpu reported outside of an error scope since the last call, and
+    /// clears it.
+    pub(crate) fn take_uncaptured_error(&self) -> Option<String> {
+        self.uncaptured_error.lock().unwrap().take()
+    }
+
+    /// Whether the device has all of `features`, e.g. optional features that were requested
+    /// through [`RendererConfig::optional_features`].
+    pub fn has_features(&self, features: wgpu::Features) -> bool {
+        self.device.features().contains(features)
+    }
+
+    /// Creates a context that does not need a surface.
+    ///
+    /// Without an [`adapter`](RendererConfig::adapter) selection, a fallback (software) adapter
+    /// is used when no other one is available, so with [`RendererConfig::headless`] this also
+    /// works on machines without a display or GPU. A selection that matches no adapter fails
+    /// with [`Error::NoAdapter`] instead.
+    pub async fn new_headless(
+        config: &RendererConfig,
+        width: u32,
+        height: u32,
+        format: wgpu::TextureFormat,
+    ) -> Result<Self> {
+        let instance = wgpu::Instance::new(wgpu::InstanceDescriptor {
+            backends: config.backends,
+            ..Default::default()
+        });
+
+        let adapter = config
+            .request_adapter(&instance, None)
+            .await
+            .ok_or(Error::NoAdapter {
+                backends: config.backends,
+            })?;
+
+        Self::new(
+            instance,
+            adapter,
+            format,
+            PhysicalSize::new(width, height),
+            config,
+        )
+        .await
+    }
+}
@@ -0,0 +1,212 @@
+use std::fmt;
+use std::path::PathBuf;
+
+/// Everything that can go wrong while setting up or running an [`Application`](crate::Application).
+#[derive(Debug)]
+pub enum Error {
+    /// The event loop could not be created, or stopped with an error.
+    EventLoop(winit::error::EventLoopError),
+    /// The window could not be created.
+    Window(winit::error::OsError),
+    /// No surface could be created for the window.
+    CreateSurface(wgpu::CreateSurfaceError),
+    /// No frame could be acquired from the surface.
+    Surface(wgpu::SurfaceError),
+    /// The surface of an additional window cannot use the format the app renders in.
+    IncompatibleSurface { format: wgpu::TextureFormat },
+    /// No adapter matches the [`RendererConfig`](crate::RendererConfig).
+    NoAdapter { backends: wgpu::Backends },
+    /// The adapter lacks features that were required.
+    MissingFeatures {
+        adapter: String,
+        features: wgpu::Features,
+    },
+    /// The adapter's limits are lower than the required ones. Each entry names the limit with the
+    /// requested and supported values.
+    MissingLimits {
+        adapter: String,
+        limits: Vec<String>,
+    },
+    /// The device could not be created.
+    Device(wgpu::RequestDeviceError),
+    /// A shader failed to parse or validate.
+    Shader { label: String, message: String },
+    /// A shader failed to parse or validate, at a known line and column of its source.
+    ShaderSource {
+        label: String,
+        line: u32,
+        column: u32,
+        message: String,
+    },
+    /// A render pipeline does not match its shaders or layout.
+    Pipeline { label: String, message: String },
+    /// A resource created through the [`GpuContext`](crate::GpuContext) could not be recorded to
+    /// be created again after a device loss, e.g. because it refers to a buffer that was created
+    /// directly on the device.
+    NotRestorable { label: String, message: String },
+    /// wgpu reported an error outside of an error scope, e.g. a validation error while a frame
+    /// was encoded or submitted.
+    Gpu { message: String },
+    /// A texture, model or other asset could not be loaded.
+    Asset {
+        name: String,
+        source: Box<dyn std::error::Error + Send + Sync>,
+    },
+    /// A file could not be read, or its contents could not be parsed.
+    Read {
+        path: PathBuf,
+        source: Box<dyn std::error::Error + Send + Sync>,
+    },
+    /// A file or directory could not be written.
+    Write {
+        path: PathBuf,
+        source: Box<dyn std::error::Error + Send + Sync>,
+    },
+    /// A frame could not be copied back from the GPU.
+    Capture { message: String },
+    /// A frame could not be added to a recording.
+    Record { message: String },
+    /// A key binding names no known key or mouse button.
+    UnknownBinding { name: String },
+}
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+impl Error {
+    pub fn asset(
+        name: impl Into<String>,
+        source: impl Into<Box<dyn std::error::Error + Send + Sync>>,
+    ) -> Self {
+        Self::Asset {
+            name: name.into(),
+            source: source.into(),
+        }
+    }
+
+    pub(crate) fn read(
+        path: impl Into<PathBuf>,
+        source: impl Into<Box<dyn std::error::Error + Send + Sync>>,
+    ) -> Self {
+        Self::Read {
+            path: path.into(),
+            source: source.into(),
+        }
+    }
+
+    pub(crate) fn write(
+        path: impl Into<PathBuf>,
+        source: impl Into<Box<dyn std::error::Error + Send + Sync>>,
+    ) -> Self {
+        Self::Write {
+            path: path.into(),
+            source: source.into(),
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::EventLoop(e) => write!(f, "event loop error: {e}"),
+            Self::Window(e) => write!(f, "failed to create the window: {e}"),
+            Self::CreateSurface(e) => write!(f, "failed to create the surface: {e}"),
+            Self::Surface(e) => write!(f, "failed to acquire a frame: {e}"),
+            Self::IncompatibleSurface { format } => {
+                write!(f, "the window's surface does not support {format:?}")
+            }
+            Self::NoAdapter { backends } => write!(f, "no adapter found for {backends:?}"),
+            Self::MissingFeatures { adapter, features } => write!(
+                f,
+                "adapter {adapter:?} does not support the required features {features:?}"
+            ),
+            Self::MissingLimits { adapter, limits } => write!(
+                f,
+                "adapter {adapter:?} does not support the required limits: {}",
+                limits.join(", ")
+            ),
+            Self::Device(e) => write!(f, "failed to create the device: {e}"),
+            Self::Shader { label, message } => write!(f, "shader {label:?} is invalid: {message}"),
+            Self::ShaderSource {
+                label,
+                line,
+                column,
+                message,
+            } => write!(
+                f,
+                "shader {label:?} is invalid at {line}:{column}: {message}"
+            ),
+            Self::Pipeline { label, message } => {
+                write!(f, "pipeline {label:?} is invalid: {message}")
+            }
+            Self::NotRestorable { label, message } => {
+                write!(f, "{label:?} cannot be created again: {message}")
+            }
+            Self::Gpu { message } => write!(f, "GPU error: {message}"),
+            Self::Asset { name, source } => write!(f, "failed to load {name:?}: {source}"),
+            Self::Read { path, source } => write!(f, "failed to read {path:?}: {source}"),
+            Self::Write { path, source } => write!(f, "failed to write {path:?}: {source}"),
+            Self::Capture { message } => write!(f, "failed to capture the frame: {message}"),
+            Self::Record { message } => write!(f, "failed to record the frame: {message}"),
+            Self::UnknownBinding { name } => {
+                write!(f, "unknown key or mouse button {name:?}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::EventLoop(e) => Some(e),
+            Self::Window(e) => Some(e),
+            Self::CreateSurface(e) => Some(e),
+            Self::Surface(e) => Some(e),
+            Self::Device(e) => Some(e),
+            Self::Asset { source, .. } | Self::Read { source, .. } | Self::Write { source, .. } => {
+                Some(source.as_ref())
+            }
+            Self::IncompatibleSurface { .. }
+            | Self::NoAdapter { .. }
+            | Self::MissingFeatures { .. }
+            | Self::MissingLimits { .. }
+            | Self::Shader { .. }
+            | Self::ShaderSource { .. }
+            | Self::Pipeline { .. }
+            | Self::NotRestorable { .. }
+            | Self::Gpu { .. }
+            | Self::Capture { .. }
+            | Self::Record { .. }
+            | Self::UnknownBinding { .. } => None,
+        }
+    }
+}
+
+impl From<winit::error::EventLoopError> for Error {
+    fn from(e: winit::error::EventLoopError) -> Self {
+        Self::EventLoop(e)
+    }
+}
+
+impl From<winit::error::OsError> for Error {
+    fn from(e: winit::error::OsError) -> Self {
+        Self::Window(e)
+    }
+}
+
+impl From<wgpu::CreateSurfaceError> for Error {
+    fn from(e: wgpu::CreateSurfaceError) -> Self {
+        Self::CreateSurface(e)
+    }
+}
+
+impl From<wgpu::SurfaceError> for Error {
+    fn from(e: wgpu::SurfaceError) -> Self {
+        Self::Surface(e)
+    }
+}
+
+impl From<wgpu::RequestDeviceError> for Error {
+    fn from(e: wgpu::RequestDeviceError) -> Self {
+        Self::Device(e)
+    }
+}
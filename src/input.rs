@@ -0,0 +1,276 @@
+use crate::{Binding, Bindings};
+use cgmath::{Vector2, Zero};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use winit::dpi::PhysicalPosition;
+use winit::event::{ElementState, KeyEvent, MouseButton, MouseScrollDelta, WindowEvent};
+use winit::keyboard::{KeyCode, ModifiersState, PhysicalKey};
+
+/// Scrolling by pixels, as touchpads do, is counted in lines of this many pixels.
+const PIXELS_PER_LINE: f32 = 20.0;
+
+/// A change to the keyboard and mouse, as [`InputState`] sees it.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum InputEvent {
+    Key {
+        key: KeyCode,
+        pressed: bool,
+    },
+    MouseButton {
+        button: MouseButton,
+        pressed: bool,
+    },
+    CursorMoved(PhysicalPosition<f64>),
+    CursorLeft,
+    /// Lines scrolled, positive to the right and up.
+    Scroll(Vector2<f32>),
+    Modifiers(ModifiersState),
+    /// The window lost the keyboard focus, so releases will not be reported.
+    FocusLost,
+}
+
+impl InputEvent {
+    /// The input carried by `event`, if any. Key repeats are dropped.
+    pub fn from_window_event(event: &WindowEvent) -> Option<Self> {
+        match event {
+            WindowEvent::KeyboardInput {
+                event:
+                    KeyEvent {
+                        physical_key: PhysicalKey::Code(key),
+                        state,
+                        repeat: false,
+                        ..
+                    },
+                ..
+            } => Some(Self::Key {
+                key: *key,
+                pressed: *state == ElementState::Pressed,
+            }),
+            WindowEvent::MouseInput { state, button, .. } => Some(Self::MouseButton {
+                button: *button,
+                pressed: *state == ElementState::Pressed,
+            }),
+            WindowEvent::CursorMoved { position, .. } => Some(Self::CursorMoved(*position)),
+            WindowEvent::CursorLeft { .. } => Some(Self::CursorLeft),
+            WindowEvent::MouseWheel { delta, .. } => Some(Self::Scroll(match *delta {
+                MouseScrollDelta::LineDelta(x, y) => Vector2::new(x, y),
+                MouseScrollDelta::PixelDelta(position) => {
+                    Vector2::new(position.x as f32, position.y as f32) / PIXELS_PER_LINE
+                }
+            })),
+            WindowEvent::ModifiersChanged(modifiers) => Some(Self::Modifiers(modifiers.state())),
+            WindowEvent::Focused(false) => Some(Self::FocusLost),
+            _ => None,
+        }
+    }
+}
+
+/// The keyboard and mouse as of the latest event: which keys and buttons are held, where the
+/// cursor is, and what changed since the previous update.
+///
+/// The runner keeps one in [`GpuContext::input`](crate::GpuContext::input). The edges, the keys
+/// and buttons just pressed or released, the cursor movement and the scrolling, build up until
+/// an update has run, so every fixed-rate update sees each press exactly once, however many
+/// updates a frame runs.
+///
+/// Actions are looked up in its [`Bindings`]: an action is pressed while any of its keys or
+/// buttons is.
+#[derive(Clone, Debug)]
+pub struct InputState {
+    bindings: Bindings,
+    keys: HashSet<KeyCode>,
+    keys_pressed: HashSet<KeyCode>,
+    keys_released: HashSet<KeyCode>,
+    buttons: HashSet<MouseButton>,
+    buttons_pressed: HashSet<MouseButton>,
+    buttons_released: HashSet<MouseButton>,
+    cursor: Option<PhysicalPosition<f64>>,
+    cursor_delta: Vector2<f64>,
+    scroll: Vector2<f32>,
+    modifiers: ModifiersState,
+}
+
+impl Default for InputState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl InputState {
+    pub fn new() -> Self {
+        Self {
+            bindings: Bindings::default(),
+            keys: HashSet::new(),
+            keys_pressed: HashSet::new(),
+            keys_released: HashSet::new(),
+            buttons: HashSet::new(),
+            buttons_pressed: HashSet::new(),
+            buttons_released: HashSet::new(),
+            cursor: None,
+            cursor_delta: Vector2::zero(),
+            scroll: Vector2::zero(),
+            modifiers: ModifiersState::empty(),
+        }
+    }
+
+    /// Applies the input carried by `event`. Returns `false` if it carried none.
+    pub fn handle_event(&mut self, event: &WindowEvent) -> bool {
+        match InputEvent::from_window_event(event) {
+            Some(event) => {
+                self.apply(&event);
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn apply(&mut self, event: &InputEvent) {
+        match *event {
+            InputEvent::Key { key, pressed } => {
+                press(
+                    &mut self.keys,
+                    &mut self.keys_pressed,
+                    &mut self.keys_released,
+                    key,
+                    pressed,
+                );
+            }
+            InputEvent::MouseButton { button, pressed } => {
+                press(
+                    &mut self.buttons,
+                    &mut self.buttons_pressed,
+                    &mut self.buttons_released,
+                    button,
+                    pressed,
+                );
+            }
+            InputEvent::CursorMoved(position) => {
+                if let Some(previous) = self.cursor {
+                    self.cursor_delta +=
+                        Vector2::new(position.x - previous.x, position.y - previous.y);
+                }
+                self.cursor = Some(position);
+            }
+            // The cursor may come back anywhere: don't count the jump as movement.
+            InputEvent::CursorLeft => self.cursor = None,
+            InputEvent::Scroll(lines) => self.scroll += lines,
+            InputEvent::Modifiers(modifiers) => self.modifiers = modifiers,
+            InputEvent::FocusLost => {
+                self.keys_released.extend(self.keys.drain());
+                self.buttons_released.extend(self.buttons.drain());
+                self.modifiers = ModifiersState::empty();
+            }
+        }
+    }
+
+    /// Forgets the edges, the cursor movement and the scrolling, keeping what is held. The
+    /// [`State`](crate::State) calls this after every update.
+    pub fn end_frame(&mut self) {
+        self.keys_pressed.clear();
+        self.keys_released.clear();
+        self.buttons_pressed.clear();
+        self.buttons_released.clear();
+        self.cursor_delta = Vector2::zero();
+        self.scroll = Vector2::zero();
+    }
+
+    /// Whether `key` is held down.
+    pub fn is_key_pressed(&self, key: KeyCode) -> bool {
+        self.keys.contains(&key)
+    }
+
+    /// Whether `key` went down since the previous update.
+    pub fn is_key_just_pressed(&self, key: KeyCode) -> bool {
+        self.keys_pressed.contains(&key)
+    }
+
+    /// Whether `key` went up since the previous update.
+    pub fn is_key_just_released(&self, key: KeyCode) -> bool {
+        self.keys_released.contains(&key)
+    }
+
+    pub fn is_mouse_pressed(&self, button: MouseButton) -> bool {
+        self.buttons.contains(&button)
+    }
+
+    pub fn is_mouse_just_pressed(&self, button: MouseButton) -> bool {
+        self.buttons_pressed.contains(&button)
+    }
+
+    pub fn is_mouse_just_released(&self, button: MouseButton) -> bool {
+        self.buttons_released.contains(&button)
+    }
+
+    /// Where the cursor is in the window, `None` when it is outside.
+    pub fn cursor_position(&self) -> Option<PhysicalPosition<f64>> {
+        self.cursor
+    }
+
+    /// How far the cursor moved since the previous update, in physical pixels.
+    pub fn cursor_delta(&self) -> Vector2<f64> {
+        self.cursor_delta
+    }
+
+    /// Lines scrolled since the previous update, positive to the right and up.
+    pub fn scroll_delta(&self) -> Vector2<f32> {
+        self.scroll
+    }
+
+    pub fn modifiers(&self) -> ModifiersState {
+        self.modifiers
+    }
+
+    pub fn bindings(&self) -> &Bindings {
+        &self.bindings
+    }
+
+    pub fn set_bindings(&mut self, bindings: Bindings) {
+        self.bindings = bindings;
+    }
+
+    /// Whether a key or button bound to `action` is held down.
+    pub fn is_action_pressed(&self, action: &str) -> bool {
+        self.any_binding(action, |binding| match binding {
+            Binding::Key(key) => self.is_key_pressed(key),
+            Binding::Mouse(button) => self.is_mouse_pressed(button),
+        })
+    }
+
+    /// Whether a key or button bound to `action` went down since the previous update.
+    pub fn is_action_just_pressed(&self, action: &str) -> bool {
+        self.any_binding(action, |binding| match binding {
+            Binding::Key(key) => self.is_key_just_pressed(key),
+            Binding::Mouse(button) => self.is_mouse_just_pressed(button),
+        })
+    }
+
+    /// Whether a key or button bound to `action` went up since the previous update.
+    pub fn is_action_just_released(&self, action: &str) -> bool {
+        self.any_binding(action, |binding| match binding {
+            Binding::Key(key) => self.is_key_just_released(key),
+            Binding::Mouse(button) => self.is_mouse_just_released(button),
+        })
+    }
+
+    fn any_binding(&self, action: &str, f: impl Fn(Binding) -> bool) -> bool {
+        self.bindings.get(action).iter().copied().any(f)
+    }
+}
+
+/// Moves `item` in or out of the held set, recording the edge. Presses of what is already held
+/// and releases of what is not are no edges.
+fn press<T: Copy + Eq + std::hash::Hash>(
+    held: &mut HashSet<T>,
+    pressed: &mut HashSet<T>,
+    released: &mut HashSet<T>,
+    item: T,
+    down: bool,
+) {
+    if down {
+        if held.insert(item) {
+            pressed.insert(item);
+        }
+    } else if held.remove(&item) {
+        released.insert(item);
+    }
+}
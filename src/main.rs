@@ -1,3 +1,59 @@
+use wgpu_template::{Application, Frame, GpuContext};
+use winit::event::WindowEvent;
+
 fn main() -> Result<(), impl std::error::Error> {
-    wgpu_template::run()
+    wgpu_template::run::<App>()
+}
+
+struct App {
+    background_color: wgpu::Color,
+}
+
+impl Application for App {
+    fn init(_ctx: &GpuContext) -> wgpu_template::Result<Self> {
+        let background_color = wgpu::Color {
+            r: 0.0,
+            g: 0.0,
+            b: 0.0,
+            a: 1.0,
+        };
+
+        Ok(Self { background_color })
+    }
+
+    fn input(&mut self, ctx: &GpuContext, event: &WindowEvent) -> bool {
+        match event {
+            WindowEvent::CursorMoved { position, .. } => {
+                let size = ctx.size;
+                self.background_color.r = position.x / size.width as f64;
+                self.background_color.g = position.y / size.height as f64;
+                self.background_color.b =
+                    (position.x + position.y) / (size.height + size.height) as f64;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn render(&mut self, frame: &mut Frame) {
+        let render_pass = frame
+            .encoder
+            .begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Render Pass"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: frame.view,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(self.background_color),
+                        store: wgpu::StoreOp::Store,
+                    },
+                })],
+                depth_stencil_attachment: None,
+                occlusion_query_set: None,
+                timestamp_writes: frame.profiler.render_pass_timestamps("Render Pass"),
+            });
+
+        // begin_render_pass() borrows encoder mutably (aka &mut self). We can't call encoder.finish() until we release that mutable borrow.
+        drop(render_pass);
+    }
 }
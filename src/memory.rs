@@ -0,0 +1,290 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::ops::Deref;
+use std::sync::{Arc, Mutex};
+
+/// What a GPU allocation is used for, to group the [`MemoryTracker`] totals.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum MemoryCategory {
+    Vertex,
+    Index,
+    Uniform,
+    Storage,
+    Texture,
+    Depth,
+    Other,
+}
+
+impl MemoryCategory {
+    /// The category of a buffer, from the first of index, vertex, uniform and storage usage it
+    /// has.
+    pub fn of_buffer(usage: wgpu::BufferUsages) -> Self {
+        if usage.contains(wgpu::BufferUsages::INDEX) {
+            Self::Index
+        } else if usage.contains(wgpu::BufferUsages::VERTEX) {
+            Self::Vertex
+        } else if usage.contains(wgpu::BufferUsages::UNIFORM) {
+            Self::Uniform
+        } else if usage.contains(wgpu::BufferUsages::STORAGE) {
+            Self::Storage
+        } else {
+            Self::Other
+        }
+    }
+
+    pub fn of_texture(format: wgpu::TextureFormat) -> Self {
+        if format.has_depth_aspect() {
+            Self::Depth
+        } else {
+            Self::Texture
+        }
+    }
+}
+
+/// One live allocation.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Allocation {
+    pub label: String,
+    pub category: MemoryCategory,
+    /// The `BufferUsages` or `TextureUsages` it was created with.
+    pub usage: String,
+    pub size: u64,
+}
+
+/// Bytes allocated in a category, or in total.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct MemoryUsage {
+    pub current: u64,
+    /// The highest `current` has been.
+    pub peak: u64,
+    /// The number of live allocations.
+    pub allocations: usize,
+}
+
+impl MemoryUsage {
+    fn add(&mut self, size: u64) {
+        self.current += size;
+        self.peak = self.peak.max(self.current);
+        self.allocations += 1;
+    }
+
+    fn remove(&mut self, size: u64) {
+        self.current -= size;
+        self.allocations -= 1;
+    }
+}
+
+impl fmt::Display for MemoryUsage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mib = |bytes: u64| bytes as f64 / (1024.0 * 1024.0);
+        write!(
+            f,
+            "{:.2} MiB (peak {:.2} MiB) in {} allocations",
+            mib(self.current),
+            mib(self.peak),
+            self.allocations
+        )
+    }
+}
+
+#[derive(Default)]
+struct Ledger {
+    next_id: u64,
+    allocations: HashMap<u64, Allocation>,
+    categories: BTreeMap<MemoryCategory, MemoryUsage>,
+    total: MemoryUsage,
+}
+
+/// Keeps account of the GPU memory held by buffers and textures.
+///
+/// Allocations are recorded with [`track`](Self::track) or its buffer and texture variants,
+/// which return a [`MemoryEntry`] that releases the allocation when dropped. Keep the entry next
+/// to the resource, as [`TrackedBuffer`] and [`Texture::track`](crate::texture::Texture::track)
+/// do. Sizes are what the resources need, drivers may add padding.
+///
+/// Clones share the same ledger.
+#[derive(Clone, Default)]
+pub struct MemoryTracker {
+    ledger: Arc<Mutex<Ledger>>,
+}
+
+impl MemoryTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn track(
+        &self,
+        label: &str,
+        category: MemoryCategory,
+        usage: impl fmt::Debug,
+        size: u64,
+    ) -> MemoryEntry {
+        let mut ledger = self.ledger.lock().unwrap();
+        let id = ledger.next_id;
+        ledger.next_id += 1;
+
+        ledger.categories.entry(category).or_default().add(size);
+        ledger.total.add(size);
+        ledger.allocations.insert(
+            id,
+            Allocation {
+                label: label.to_string(),
+                category,
+                usage: format!("{usage:?}"),
+                size,
+            },
+        );
+
+        MemoryEntry {
+            ledger: Arc::clone(&self.ledger),
+            id,
+        }
+    }
+
+    pub fn track_buffer(&self, buffer: &wgpu::Buffer, label: &str) -> MemoryEntry {
+        self.track(
+            label,
+            MemoryCategory::of_buffer(buffer.usage()),
+            buffer.usage(),
+            buffer.size(),
+        )
+    }
+
+    pub fn track_texture(&self, texture: &wgpu::Texture, label: &str) -> MemoryEntry {
+        self.track(
+            label,
+            MemoryCategory::of_texture(texture.format()),
+            texture.usage(),
+            texture_size(texture),
+        )
+    }
+
+    pub fn usage(&self, category: MemoryCategory) -> MemoryUsage {
+        let ledger = self.ledger.lock().unwrap();
+        ledger
+            .categories
+            .get(&category)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    pub fn total(&self) -> MemoryUsage {
+        self.ledger.lock().unwrap().total
+    }
+
+    /// The live allocations, largest first.
+    pub fn allocations(&self) -> Vec<Allocation> {
+        let ledger = self.ledger.lock().unwrap();
+        let mut allocations: Vec<_> = ledger.allocations.values().cloned().collect();
+        allocations.sort_by(|a, b| b.size.cmp(&a.size).then_with(|| a.label.cmp(&b.label)));
+        allocations
+    }
+
+    /// The usage of every category that has been used, and the total.
+    pub fn report(&self) -> MemoryReport {
+        let ledger = self.ledger.lock().unwrap();
+        MemoryReport {
+            categories: ledger
+                .categories
+                .iter()
+                .map(|(category, usage)| (*category, *usage))
+                .collect(),
+            total: ledger.total,
+        }
+    }
+}
+
+/// A snapshot of a [`MemoryTracker`]'s totals.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MemoryReport {
+    pub categories: Vec<(MemoryCategory, MemoryUsage)>,
+    pub total: MemoryUsage,
+}
+
+impl fmt::Display for MemoryReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "GPU memory: {}", self.total)?;
+        for (category, usage) in &self.categories {
+            writeln!(f, "  {category:?}: {usage}")?;
+        }
+        Ok(())
+    }
+}
+
+/// An allocation recorded by a [`MemoryTracker`], released when dropped.
+pub struct MemoryEntry {
+    ledger: Arc<Mutex<Ledger>>,
+    id: u64,
+}
+
+impl Drop for MemoryEntry {
+    fn drop(&mut self) {
+        let mut ledger = self.ledger.lock().unwrap();
+        if let Some(allocation) = ledger.allocations.remove(&self.id) {
+            if let Some(usage) = ledger.categories.get_mut(&allocation.category) {
+                usage.remove(allocation.size);
+            }
+            ledger.total.remove(allocation.size);
+        }
+    }
+}
+
+/// A buffer whose memory is released from its [`MemoryTracker`] when it is dropped.
+pub struct TrackedBuffer {
+    buffer: wgpu::Buffer,
+    _entry: MemoryEntry,
+}
+
+impl TrackedBuffer {
+    pub fn new(buffer: wgpu::Buffer, memory: &MemoryTracker, label: &str) -> Self {
+        let entry = memory.track_buffer(&buffer, label);
+        Self {
+            buffer,
+            _entry: entry,
+        }
+    }
+}
+
+impl Deref for TrackedBuffer {
+    type Target = wgpu::Buffer;
+
+    fn deref(&self) -> &wgpu::Buffer {
+        &self.buffer
+    }
+}
+
+/// The bytes `texture` needs, over all its mip levels, layers and samples.
+fn texture_size(texture: &wgpu::Texture) -> u64 {
+    let format = texture.format();
+    let (block_width, block_height) = format.block_dimensions();
+    // Combined depth-stencil formats have no size of their own.
+    let block_size = format.block_copy_size(None).unwrap_or_else(|| {
+        format
+            .block_copy_size(Some(wgpu::TextureAspect::DepthOnly))
+            .unwrap_or(4)
+            + format
+                .block_copy_size(Some(wgpu::TextureAspect::StencilOnly))
+                .unwrap_or(0)
+    }) as u64;
+
+    let size = texture.size();
+    let layers = match texture.dimension() {
+        wgpu::TextureDimension::D3 => 1,
+        _ => size.depth_or_array_layers,
+    };
+    let level_bytes = |level: u32| {
+        let mip = size.mip_level_size(level, texture.dimension());
+        let blocks_wide = mip.width.div_ceil(block_width) as u64;
+        let blocks_high = mip.height.div_ceil(block_height) as u64;
+        let depth = match texture.dimension() {
+            wgpu::TextureDimension::D3 => mip.depth_or_array_layers as u64,
+            _ => 1,
+        };
+        blocks_wide * blocks_high * depth * block_size
+    };
+
+    (0..texture.mip_level_count()).map(level_bytes).sum::<u64>()
+        * layers as u64
+        * texture.sample_count() as u64
+}
@@ -0,0 +1,135 @@
+use std::fmt;
+use std::ops::{Deref, DerefMut, Range};
+
+/// What the render passes of one frame asked the GPU to do.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct DrawCounts {
+    pub draw_calls: u32,
+    /// Instances over all draw calls.
+    pub instances: u64,
+    /// Vertices of non-indexed draws, times their instance count.
+    pub vertices: u64,
+    /// Indices of indexed draws, times their instance count.
+    pub indices: u64,
+    /// Triangles drawn, assuming every pipeline uses a triangle list.
+    pub triangles: u64,
+    /// `set_pipeline` calls that changed the pipeline.
+    pub pipeline_switches: u32,
+    /// `set_bind_group` calls that changed the bind group of their slot.
+    pub bind_group_switches: u32,
+    /// `set_vertex_buffer` and `set_index_buffer` calls.
+    pub buffer_bindings: u32,
+}
+
+impl fmt::Display for DrawCounts {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} draws, {} instances, {} triangles, {} pipeline / {} bind group switches, {} buffer bindings",
+            self.draw_calls,
+            self.instances,
+            self.triangles,
+            self.pipeline_switches,
+            self.bind_group_switches,
+            self.buffer_bindings
+        )
+    }
+}
+
+/// A render pass that adds what is recorded into it to a [`DrawCounts`].
+///
+/// The state-setting and draw methods shadow those of [`wgpu::RenderPass`]; every other method
+/// is reachable through `Deref` and is not counted.
+pub struct InstrumentedRenderPass<'a> {
+    pass: wgpu::RenderPass<'a>,
+    counts: &'a mut DrawCounts,
+    pipeline: Option<wgpu::Id<wgpu::RenderPipeline>>,
+    bind_groups: Vec<Option<wgpu::Id<wgpu::BindGroup>>>,
+}
+
+impl<'a> InstrumentedRenderPass<'a> {
+    pub fn new(pass: wgpu::RenderPass<'a>, counts: &'a mut DrawCounts) -> Self {
+        Self {
+            pass,
+            counts,
+            pipeline: None,
+            bind_groups: Vec::new(),
+        }
+    }
+
+    pub fn set_pipeline(&mut self, pipeline: &'a wgpu::RenderPipeline) {
+        let id = pipeline.global_id();
+        if self.pipeline != Some(id) {
+            self.pipeline = Some(id);
+            self.counts.pipeline_switches += 1;
+        }
+        self.pass.set_pipeline(pipeline);
+    }
+
+    pub fn set_bind_group(
+        &mut self,
+        index: u32,
+        bind_group: &'a wgpu::BindGroup,
+        offsets: &[wgpu::DynamicOffset],
+    ) {
+        let index = index as usize;
+        if self.bind_groups.len() <= index {
+            self.bind_groups.resize(index + 1, None);
+        }
+        // Dynamic offsets change what is bound even when the bind group stays the same.
+        let id = bind_group.global_id();
+        if self.bind_groups[index] != Some(id) || !offsets.is_empty() {
+            self.bind_groups[index] = Some(id);
+            self.counts.bind_group_switches += 1;
+        }
+        self.pass.set_bind_group(index as u32, bind_group, offsets);
+    }
+
+    pub fn set_vertex_buffer(&mut self, slot: u32, buffer_slice: wgpu::BufferSlice<'a>) {
+        self.counts.buffer_bindings += 1;
+        self.pass.set_vertex_buffer(slot, buffer_slice);
+    }
+
+    pub fn set_index_buffer(
+        &mut self,
+        buffer_slice: wgpu::BufferSlice<'a>,
+        index_format: wgpu::IndexFormat,
+    ) {
+        self.counts.buffer_bindings += 1;
+        self.pass.set_index_buffer(buffer_slice, index_format);
+    }
+
+    pub fn draw(&mut self, vertices: Range<u32>, instances: Range<u32>) {
+        let instance_count = instances.len() as u64;
+        let vertex_count = vertices.len() as u64;
+        self.counts.draw_calls += 1;
+        self.counts.instances += instance_count;
+        self.counts.vertices += vertex_count * instance_count;
+        self.counts.triangles += vertex_count / 3 * instance_count;
+        self.pass.draw(vertices, instances);
+    }
+
+    pub fn draw_indexed(&mut self, indices: Range<u32>, base_vertex: i32, instances: Range<u32>) {
+        let instance_count = instances.len() as u64;
+        let index_count = indices.len() as u64;
+        self.counts.draw_calls += 1;
+        self.counts.instances += instance_count;
+        self.counts.indices += index_count * instance_count;
+        self.counts.triangles += index_count / 3 * instance_count;
+        self.pass.draw_indexed(indices, base_vertex, instances);
+    }
+}
+
+impl<'a> Deref for InstrumentedRenderPass<'a> {
+    type Target = wgpu::RenderPass<'a>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.pass
+    }
+}
+
+impl DerefMut for InstrumentedRenderPass<'_> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.pass
+    }
+}
@@ -0,0 +1,422 @@
+use log::{debug, warn};
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Frames whose timestamps can be waiting for the GPU at the same time. When they are all still
+/// in flight, the next frame is not profiled.
+const FRAMES_IN_FLIGHT: usize = 4;
+/// Timestamp queries available to one frame, two per scope.
+const QUERIES_PER_FRAME: u32 = 256;
+/// Resolved frames kept for [`GpuProfiler::frames`] and the Chrome trace.
+const HISTORY: usize = 300;
+
+/// GPU time spent in one named scope or pass.
+#[derive(Clone, Debug, PartialEq)]
+pub struct GpuScope {
+    pub label: String,
+    /// How many scopes enclose this one.
+    pub depth: u32,
+    /// When the scope started and ended on the GPU clock, which has an arbitrary origin.
+    pub start: Duration,
+    pub end: Duration,
+}
+
+impl GpuScope {
+    pub fn duration(&self) -> Duration {
+        self.end.saturating_sub(self.start)
+    }
+}
+
+/// The scopes measured during one frame, in the order they were opened.
+#[derive(Clone, Debug, PartialEq)]
+pub struct GpuFrame {
+    /// The number of the frame, counting every frame rendered, profiled or not.
+    pub index: u64,
+    pub scopes: Vec<GpuScope>,
+}
+
+impl GpuFrame {
+    /// The GPU time of the first scope called `label`.
+    pub fn duration(&self, label: &str) -> Option<Duration> {
+        self.scopes
+            .iter()
+            .find(|scope| scope.label == label)
+            .map(GpuScope::duration)
+    }
+}
+
+struct PendingScope {
+    label: String,
+    depth: u32,
+    begin: u32,
+}
+
+/// The queries of one frame and the buffers they are read back through.
+struct QueryFrame {
+    query_set: wgpu::QuerySet,
+    resolve_buffer: wgpu::Buffer,
+    readback_buffer: wgpu::Buffer,
+    index: u64,
+    scopes: Vec<PendingScope>,
+    next_query: u32,
+    /// Set by the `map_async` callback once the readback buffer can be read.
+    mapped: Arc<Mutex<Option<Result<(), wgpu::BufferAsyncError>>>>,
+    in_flight: bool,
+}
+
+impl QueryFrame {
+    fn new(device: &wgpu::Device) -> Self {
+        let size =
+            QUERIES_PER_FRAME as wgpu::BufferAddress * wgpu::QUERY_SIZE as wgpu::BufferAddress;
+        Self {
+            query_set: device.create_query_set(&wgpu::QuerySetDescriptor {
+                label: Some("Profiler Query Set"),
+                ty: wgpu::QueryType::Timestamp,
+                count: QUERIES_PER_FRAME,
+            }),
+            resolve_buffer: device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some("Profiler Resolve Buffer"),
+                size,
+                usage: wgpu::BufferUsages::QUERY_RESOLVE | wgpu::BufferUsages::COPY_SRC,
+                mapped_at_creation: false,
+            }),
+            readback_buffer: device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some("Profiler Readback Buffer"),
+                size,
+                usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+                mapped_at_creation: false,
+            }),
+            index: 0,
+            scopes: Vec::new(),
+            next_query: 0,
+            mapped: Arc::new(Mutex::new(None)),
+            in_flight: false,
+        }
+    }
+
+    fn allocate(&mut self, label: &str, depth: u32) -> Option<u32> {
+        if self.next_query + 2 > QUERIES_PER_FRAME {
+            return None;
+        }
+        let begin = self.next_query;
+        self.next_query += 2;
+        self.scopes.push(PendingScope {
+            label: label.to_string(),
+            depth,
+            begin,
+        });
+        Some(begin)
+    }
+
+    fn readback_size(&self) -> wgpu::BufferAddress {
+        self.next_query as wgpu::BufferAddress * wgpu::QUERY_SIZE as wgpu::BufferAddress
+    }
+
+    /// Reads the resolved timestamps, once the readback buffer is mapped, and frees the frame.
+    fn collect(&mut self, period: f32) -> Option<GpuFrame> {
+        let mapped = self.mapped.lock().unwrap().take()?;
+        self.in_flight = false;
+
+        let frame = match mapped {
+            Ok(()) => {
+                let slice = self.readback_buffer.slice(..self.readback_size());
+                let timestamps: Vec<u64> = bytemuck::cast_slice(&slice.get_mapped_range()).to_vec();
+                self.readback_buffer.unmap();
+
+                let time = |ticks: u64| Duration::from_nanos((ticks as f64 * period as f64) as u64);
+                Some(GpuFrame {
+                    index: self.index,
+                    scopes: self
+                        .scopes
+                        .drain(..)
+                        .map(|scope| GpuScope {
+                            start: time(timestamps[scope.begin as usize]),
+                            end: time(timestamps[scope.begin as usize + 1]),
+                            label: scope.label,
+                            depth: scope.depth,
+                        })
+                        .collect(),
+                })
+            }
+            Err(e) => {
+                warn!("Failed to read the timestamps of frame {}: {e}", self.index);
+                None
+            }
+        };
+        self.scopes.clear();
+        self.next_query = 0;
+        frame
+    }
+}
+
+/// Measures how long named passes and scopes take on the GPU, using timestamp queries.
+///
+/// Needs the `TIMESTAMP_QUERY` feature, which [`RendererConfig::gpu_profiling`] requests; scopes
+/// recorded directly on an encoder also need `TIMESTAMP_QUERY_INSIDE_ENCODERS`. Without them
+/// every method is a no-op and passes get no timestamp writes.
+///
+/// Timestamps are read back asynchronously, so a frame's timings become available a few frames
+/// after it was rendered.
+///
+/// [`RendererConfig::gpu_profiling`]: crate::RendererConfig::gpu_profiling
+pub struct GpuProfiler {
+    enabled: bool,
+    inside_encoders: bool,
+    /// Nanoseconds per timestamp tick.
+    period: f32,
+    pool: Vec<QueryFrame>,
+    /// The pool entry recording the current frame, if it is profiled.
+    current: Option<usize>,
+    /// Indices into the current frame's scopes of the scopes not ended yet.
+    open_scopes: Vec<usize>,
+    frame_index: u64,
+    history: VecDeque<GpuFrame>,
+}
+
+impl GpuProfiler {
+    pub fn new(device: &wgpu::Device, queue: &wgpu::Queue) -> Self {
+        let features = device.features();
+        Self {
+            enabled: features.contains(wgpu::Features::TIMESTAMP_QUERY),
+            inside_encoders: features.contains(wgpu::Features::TIMESTAMP_QUERY_INSIDE_ENCODERS),
+            period: queue.get_timestamp_period(),
+            pool: Vec::new(),
+            current: None,
+            open_scopes: Vec::new(),
+            frame_index: 0,
+            history: VecDeque::new(),
+        }
+    }
+
+    /// Whether the device supports timestamp queries.
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// The most recent frame whose timestamps were read back.
+    pub fn latest(&self) -> Option<&GpuFrame> {
+        self.history.back()
+    }
+
+    /// The frames read back so far, oldest first, up to the last few hundred.
+    pub fn frames(&self) -> impl Iterator<Item = &GpuFrame> {
+        self.history.iter()
+    }
+
+    /// Timestamp writes for a render pass, measured as a scope called `label`. Pass the result
+    /// as `RenderPassDescriptor::timestamp_writes`.
+    pub fn render_pass_timestamps(
+        &mut self,
+        label: &str,
+    ) -> Option<wgpu::RenderPassTimestampWrites<'_>> {
+        let (query_set, begin) = self.allocate(label)?;
+        Some(wgpu::RenderPassTimestampWrites {
+            query_set,
+            beginning_of_pass_write_index: Some(begin),
+            end_of_pass_write_index: Some(begin + 1),
+        })
+    }
+
+    /// Timestamp writes for a compute pass, measured as a scope called `label`.
+    pub fn compute_pass_timestamps(
+        &mut self,
+        label: &str,
+    ) -> Option<wgpu::ComputePassTimestampWrites<'_>> {
+        let (query_set, begin) = self.allocate(label)?;
+        Some(wgpu::ComputePassTimestampWrites {
+            query_set,
+            beginning_of_pass_write_index: Some(begin),
+            end_of_pass_write_index: Some(begin + 1),
+        })
+    }
+
+    /// Starts a scope called `label` around the commands recorded next into `encoder`. Scopes
+    /// nest, and each must be closed with [`end_scope`](Self::end_scope) on the same encoder.
+    pub fn begin_scope(&mut self, label: &str, encoder: &mut wgpu::CommandEncoder) {
+        if !self.inside_encoders {
+            return;
+        }
+        let Some(frame) = self.current.map(|i| &mut self.pool[i]) else {
+            return;
+        };
+        let depth = self.open_scopes.len() as u32;
+        match frame.allocate(label, depth) {
+            Some(begin) => {
+                encoder.write_timestamp(&frame.query_set, begin);
+                self.open_scopes.push(frame.scopes.len() - 1);
+            }
+            None => warn!("Too many GPU scopes in one frame, {label:?} is not measured"),
+        }
+    }
+
+    pub fn end_scope(&mut self, encoder: &mut wgpu::CommandEncoder) {
+        let (Some(frame), Some(scope)) =
+            (self.current.map(|i| &self.pool[i]), self.open_scopes.pop())
+        else {
+            return;
+        };
+        encoder.write_timestamp(&frame.query_set, frame.scopes[scope].begin + 1);
+    }
+
+    fn allocate(&mut self, label: &str) -> Option<(&wgpu::QuerySet, u32)> {
+        let depth = self.open_scopes.len() as u32;
+        let frame = &mut self.pool[self.current?];
+        match frame.allocate(label, depth) {
+            Some(begin) => Some((&frame.query_set, begin)),
+            None => {
+                warn!("Too many GPU scopes in one frame, {label:?} is not measured");
+                None
+            }
+        }
+    }
+
+    /// Collects the frames whose timestamps are ready and starts profiling a new frame.
+    pub(crate) fn begin_frame(&mut self, device: &wgpu::Device) {
+        self.frame_index += 1;
+        if !self.enabled {
+            return;
+        }
+
+        device.poll(wgpu::Maintain::Poll);
+        self.collect();
+
+        let free = self.pool.iter().position(|frame| !frame.in_flight);
+        self.current = match free {
+            Some(i) => Some(i),
+            None if self.pool.len() < FRAMES_IN_FLIGHT => {
+                self.pool.push(QueryFrame::new(device));
+                Some(self.pool.len() - 1)
+            }
+            None => {
+                debug!(
+                    "Every profiler frame is in flight, skipping frame {}",
+                    self.frame_index
+                );
+                None
+            }
+        };
+        if let Some(i) = self.current {
+            let frame = &mut self.pool[i];
+            frame.index = self.frame_index;
+            // Left over when the previous frame was never submitted.
+            frame.scopes.clear();
+            frame.next_query = 0;
+        }
+        self.open_scopes.clear();
+    }
+
+    /// Closes the scopes left open and records the copy of the frame's timestamps into a
+    /// readable buffer.
+    pub(crate) fn end_frame(&mut self, encoder: &mut wgpu::CommandEncoder) {
+        while !self.open_scopes.is_empty() {
+            let frame = &self.pool[self.current.unwrap()];
+            warn!(
+                "GPU scope {:?} was not ended",
+                frame.scopes[*self.open_scopes.last().unwrap()].label
+            );
+            self.end_scope(encoder);
+        }
+
+        let Some(frame) = self.current.map(|i| &self.pool[i]) else {
+            return;
+        };
+        if frame.next_query == 0 {
+            return;
+        }
+        encoder.resolve_query_set(
+            &frame.query_set,
+            0..frame.next_query,
+            &frame.resolve_buffer,
+            0,
+        );
+        encoder.copy_buffer_to_buffer(
+            &frame.resolve_buffer,
+            0,
+            &frame.readback_buffer,
+            0,
+            frame.readback_size(),
+        );
+    }
+
+    /// Starts reading back the timestamps of the frame that was just submitted.
+    pub(crate) fn frame_submitted(&mut self) {
+        let Some(frame) = self.current.take().map(|i| &mut self.pool[i]) else {
+            return;
+        };
+        if frame.next_query == 0 {
+            return;
+        }
+        frame.in_flight = true;
+        let mapped = Arc::clone(&frame.mapped);
+        frame
+            .readback_buffer
+            .slice(..frame.readback_size())
+            .map_async(wgpu::MapMode::Read, move |result| {
+                *mapped.lock().unwrap() = Some(result);
+            });
+    }
+
+    /// Waits for the GPU and reads back every frame still in flight.
+    pub fn flush(&mut self, device: &wgpu::Device) {
+        if self.pool.iter().any(|frame| frame.in_flight) {
+            device.poll(wgpu::Maintain::Wait);
+            self.collect();
+        }
+    }
+
+    fn collect(&mut self) {
+        let mut ready: Vec<GpuFrame> = self
+            .pool
+            .iter_mut()
+            .filter(|frame| frame.in_flight)
+            .filter_map(|frame| frame.collect(self.period))
+            .collect();
+        ready.sort_by_key(|frame| frame.index);
+
+        for frame in ready {
+            if self.history.len() == HISTORY {
+                self.history.pop_front();
+            }
+            self.history.push_back(frame);
+        }
+    }
+
+    /// The frames read back so far as Chrome trace JSON, viewable in `chrome://tracing` or
+    /// Perfetto. Times are relative to the first scope.
+    pub fn chrome_trace(&self) -> String {
+        let origin = self
+            .history
+            .iter()
+            .flat_map(|frame| &frame.scopes)
+            .map(|scope| scope.start)
+            .min()
+            .unwrap_or_default();
+        let micros = |duration: Duration| duration.as_secs_f64() * 1e6;
+
+        let events: Vec<serde_json::Value> = self
+            .history
+            .iter()
+            .flat_map(|frame| {
+                frame.scopes.iter().map(move |scope| {
+                    serde_json::json!({
+                        "name": scope.label,
+                        "cat": "gpu",
+                        "ph": "X",
+                        "ts": micros(scope.start.saturating_sub(origin)),
+                        "dur": micros(scope.duration()),
+                        "pid": 0,
+                        "tid": 0,
+                        "args": { "frame": frame.index, "depth": scope.depth },
+                    })
+                })
+            })
+            .collect();
+
+        serde_json::json!({ "traceEvents": events, "displayTimeUnit": "ms" }).to_string()
+    }
+
+    pub fn write_chrome_trace(&self, path: impl AsRef<std::path::Path>) -> std::io::Result<()> {
+        std::fs::write(path, self.chrome_trace())
+    }
+}
@@ -0,0 +1,266 @@
+use crate::{Error, Result};
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// How a [`Recording`] is stored.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RecordFormat {
+    /// One PNG per frame, `frame_00000.png` onwards, in a directory.
+    Pngs,
+    /// An animated GIF. Colors are quantized to a 256-color palette per frame.
+    Gif,
+    /// An animated PNG, lossless.
+    Apng,
+}
+
+impl RecordFormat {
+    /// The format implied by `path`: `.gif` and `.png`/`.apng` files are animations, anything
+    /// else is a directory of numbered PNGs.
+    pub fn from_path(path: &Path) -> Self {
+        let extension = path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .map(str::to_lowercase);
+        match extension.as_deref() {
+            Some("gif") => Self::Gif,
+            Some("png" | "apng") => Self::Apng,
+            _ => Self::Pngs,
+        }
+    }
+}
+
+/// What to record: how many frames, at which simulated rate, and where to.
+///
+/// Frames are spaced by a fixed simulated timestep, `1 / fps`, whatever the real frame rate
+/// is, so recordings are smooth and repeatable even when rendering is slow.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Recording {
+    pub(crate) path: PathBuf,
+    pub(crate) format: RecordFormat,
+    pub(crate) frames: u32,
+    pub(crate) fps: f64,
+    pub(crate) start: Duration,
+}
+
+impl Default for Recording {
+    fn default() -> Self {
+        Self::new("recording.gif")
+    }
+}
+
+impl Recording {
+    /// Two seconds at 30 frames per second, in the format implied by `path`.
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+        Self {
+            format: RecordFormat::from_path(&path),
+            path,
+            frames: 60,
+            fps: 30.0,
+            start: Duration::ZERO,
+        }
+    }
+
+    pub fn format(mut self, format: RecordFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    pub fn frames(mut self, frames: u32) -> Self {
+        self.frames = frames;
+        self
+    }
+
+    /// The simulated frame rate. It must be positive, recording at another one fails.
+    pub fn fps(mut self, fps: f64) -> Self {
+        self.fps = fps;
+        self
+    }
+
+    /// Records as many frames as fit in `duration` at the current frame rate.
+    pub fn duration(mut self, duration: Duration) -> Self {
+        self.frames = (duration.as_secs_f64() * self.fps).ceil() as u32;
+        self
+    }
+
+    /// Simulated time that passes before the first frame is recorded, to record a time range
+    /// `start..start + duration`.
+    pub fn start(mut self, start: Duration) -> Self {
+        self.start = start;
+        self
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn frame_count(&self) -> u32 {
+        self.frames
+    }
+
+    pub fn start_time(&self) -> Duration {
+        self.start
+    }
+
+    /// The simulated time between two frames.
+    pub fn frame_time(&self) -> Duration {
+        Duration::from_secs_f64(1.0 / self.fps)
+    }
+}
+
+/// Writes the frames of a [`Recording`] as they are captured.
+pub struct Recorder {
+    recording: Recording,
+    /// Created with the first frame, whose size it takes.
+    sink: Option<(Sink, (u32, u32))>,
+    recorded: u32,
+}
+
+enum Sink {
+    Pngs,
+    Gif(gif::Encoder<BufWriter<File>>),
+    Apng(png::Writer<BufWriter<File>>),
+}
+
+impl Recorder {
+    /// Fails if the recording's frame rate is not positive.
+    pub fn new(recording: Recording) -> Result<Self> {
+        // Also rules out NaN.
+        if !(recording.fps > 0.0 && recording.fps.is_finite()) {
+            return Err(Error::Record {
+                message: format!("the frame rate must be positive, not {}", recording.fps),
+            });
+        }
+        Ok(Self {
+            recording,
+            sink: None,
+            recorded: 0,
+        })
+    }
+
+    pub fn recording(&self) -> &Recording {
+        &self.recording
+    }
+
+    /// The number of frames written so far.
+    pub fn recorded(&self) -> u32 {
+        self.recorded
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.recorded >= self.recording.frames
+    }
+
+    /// Writes the next frame. Every frame must have the size of the first.
+    pub fn push(&mut self, image: &image::RgbaImage) -> Result<()> {
+        if self.is_done() {
+            return Err(Error::Record {
+                message: "the recording already has all its frames".to_string(),
+            });
+        }
+        if self.sink.is_none() {
+            let sink = self.create_sink(image.width(), image.height())?;
+            self.sink = Some((sink, image.dimensions()));
+        }
+        let (sink, size) = self.sink.as_mut().unwrap();
+        if image.dimensions() != *size {
+            return Err(Error::Record {
+                message: format!(
+                    "the frame is {:?}, but the recording is {size:?}",
+                    image.dimensions()
+                ),
+            });
+        }
+
+        let path = &self.recording.path;
+        match sink {
+            Sink::Pngs => {
+                let frame_path = path.join(format!("frame_{:05}.png", self.recorded));
+                image
+                    .save_with_format(&frame_path, image::ImageFormat::Png)
+                    .map_err(|e| Error::write(&frame_path, e))?;
+            }
+            Sink::Gif(encoder) => {
+                let (width, height) = gif_size(image)?;
+                let mut pixels = image.as_raw().clone();
+                let mut frame = gif::Frame::from_rgba_speed(width, height, &mut pixels, 10);
+                frame.delay = (100.0 / self.recording.fps).round() as u16;
+                encoder
+                    .write_frame(&frame)
+                    .map_err(|e| Error::write(path, e))?;
+            }
+            Sink::Apng(writer) => writer
+                .write_image_data(image.as_raw())
+                .map_err(|e| Error::write(path, e))?,
+        }
+        self.recorded += 1;
+        Ok(())
+    }
+
+    /// Completes the file and returns where the recording is.
+    pub fn finish(self) -> Result<PathBuf> {
+        let path = self.recording.path;
+        match self.sink.map(|(sink, _)| sink) {
+            Some(Sink::Apng(writer)) => writer.finish().map_err(|e| Error::write(&path, e))?,
+            // Dropping the encoder writes the GIF trailer.
+            Some(Sink::Gif(encoder)) => drop(encoder),
+            Some(Sink::Pngs) | None => {}
+        }
+        Ok(path)
+    }
+
+    fn create_sink(&self, width: u32, height: u32) -> Result<Sink> {
+        let path = &self.recording.path;
+        if self.recording.format == RecordFormat::Pngs {
+            std::fs::create_dir_all(path).map_err(|e| Error::write(path, e))?;
+            return Ok(Sink::Pngs);
+        }
+
+        if let Some(parent) = path
+            .parent()
+            .filter(|parent| !parent.as_os_str().is_empty())
+        {
+            std::fs::create_dir_all(parent).map_err(|e| Error::write(parent, e))?;
+        }
+        let file = File::create(path).map_err(|e| Error::write(path, e))?;
+        let writer = BufWriter::new(file);
+
+        if self.recording.format == RecordFormat::Gif {
+            let (width, height) = (gif_dimension(width)?, gif_dimension(height)?);
+            let write_error = |e| Error::write(path, e);
+            let mut encoder = gif::Encoder::new(writer, width, height, &[]).map_err(write_error)?;
+            encoder
+                .set_repeat(gif::Repeat::Infinite)
+                .map_err(write_error)?;
+            return Ok(Sink::Gif(encoder));
+        }
+
+        let write_error = |e| Error::write(path, e);
+        let mut encoder = png::Encoder::new(writer, width, height);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        encoder
+            .set_animated(self.recording.frames, 0)
+            .map_err(write_error)?;
+        // Frame delays are fractions of a second; a thousandth is precise enough.
+        encoder
+            .set_frame_delay((1000.0 / self.recording.fps).round() as u16, 1000)
+            .map_err(write_error)?;
+        Ok(Sink::Apng(encoder.write_header().map_err(write_error)?))
+    }
+}
+
+fn gif_size(image: &image::RgbaImage) -> Result<(u16, u16)> {
+    Ok((
+        gif_dimension(image.width())?,
+        gif_dimension(image.height())?,
+    ))
+}
+
+fn gif_dimension(pixels: u32) -> Result<u16> {
+    u16::try_from(pixels).map_err(|_| Error::Record {
+        message: "GIF frames are at most 65535 pixels wide and high".to_string(),
+    })
+}
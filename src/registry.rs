@@ -0,0 +1,647 @@
+use crate::texture::{Texture, TextureOptions};
+use crate::{Error, GpuContext, Result, TrackedBuffer};
+use std::any::Any;
+use std::collections::HashMap;
+use std::fmt;
+use std::marker::PhantomData;
+use std::num::NonZeroU32;
+use std::sync::{Arc, Mutex, RwLock, Weak};
+
+/// The object of a resource, with the recipe it is created from, so that the resources the
+/// recipe refers to are released along with it.
+struct Slot<T> {
+    object: RwLock<Arc<T>>,
+    recipe: Box<dyn Any + Send + Sync>,
+}
+
+/// A GPU object created through a [`GpuContext`], which [`State::recover`](crate::State::recover)
+/// creates again on the new device after a device loss.
+///
+/// The handle stays the same, [`get`](Self::get) returns the object on the current device.
+/// Render passes borrow what is bound to them, so keep what `get` returns in a local declared
+/// before the pass. Clones share the same object.
+pub struct Restorable<T> {
+    slot: Arc<Slot<T>>,
+}
+
+impl<T> Restorable<T> {
+    pub fn get(&self) -> Arc<T> {
+        Arc::clone(&self.slot.object.read().unwrap())
+    }
+}
+
+impl<T> Clone for Restorable<T> {
+    fn clone(&self) -> Self {
+        Self {
+            slot: Arc::clone(&self.slot),
+        }
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for Restorable<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Restorable").field(&self.get()).finish()
+    }
+}
+
+/// How a resource is created: its descriptor, with the resources it refers to as handles, and
+/// the data it is filled with.
+pub(crate) trait Recipe: Send + Sync + 'static {
+    type Object: Send + Sync + 'static;
+
+    fn create(&self, ctx: &GpuContext) -> Self::Object;
+}
+
+struct Entry<R: Recipe> {
+    slot: Weak<Slot<R::Object>>,
+    recipe: PhantomData<fn() -> R>,
+}
+
+trait Replay: Send {
+    /// Creates the resource again on `ctx`'s device. Returns `false`, doing nothing, once all
+    /// its handles were dropped.
+    fn replay(&self, ctx: &GpuContext) -> bool;
+
+    fn is_live(&self) -> bool;
+
+    fn as_any(&self) -> &dyn Any;
+}
+
+impl<R: Recipe> Replay for Entry<R> {
+    fn replay(&self, ctx: &GpuContext) -> bool {
+        let Some(slot) = self.slot.upgrade() else {
+            return false;
+        };
+        let recipe = slot.recipe.downcast_ref::<R>().unwrap();
+        let object = recipe.create(ctx);
+        *slot.object.write().unwrap() = Arc::new(object);
+        true
+    }
+
+    fn is_live(&self) -> bool {
+        self.slot.strong_count() > 0
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+/// The recipes of the live resources created through a [`GpuContext`], in the order they were
+/// created, so that those a resource refers to are created again before it.
+///
+/// Clones share the same entries.
+#[derive(Clone, Default)]
+pub(crate) struct Registry {
+    entries: Arc<Mutex<Vec<Box<dyn Replay>>>>,
+}
+
+impl Registry {
+    /// Creates a resource from `recipe` on `ctx`'s device and records it.
+    pub(crate) fn create<R: Recipe>(&self, ctx: &GpuContext, recipe: R) -> Restorable<R::Object> {
+        let object = recipe.create(ctx);
+        self.register(object, recipe)
+    }
+
+    /// Records `object`, which was created from `recipe`.
+    pub(crate) fn register<R: Recipe>(
+        &self,
+        object: R::Object,
+        recipe: R,
+    ) -> Restorable<R::Object> {
+        let restorable = Restorable {
+            slot: Arc::new(Slot {
+                object: RwLock::new(Arc::new(object)),
+                recipe: Box::new(recipe),
+            }),
+        };
+        let mut entries = self.entries.lock().unwrap();
+        // Forget the resources that were dropped in the meantime.
+        entries.retain(|entry| entry.is_live());
+        entries.push(Box::new(Entry::<R> {
+            slot: Arc::downgrade(&restorable.slot),
+            recipe: PhantomData,
+        }));
+        restorable
+    }
+
+    /// Creates all live resources again on `ctx`'s device, in the order they were created.
+    pub(crate) fn replay(&self, ctx: &GpuContext) {
+        let mut entries = self.entries.lock().unwrap();
+        entries.retain(|entry| entry.replay(ctx));
+    }
+
+    /// The handle of the live resource made by an `R` whose object `matches`, or an error
+    /// saying that the resource `label` refers to a `kind` not created through the context.
+    fn find<R: Recipe>(
+        &self,
+        label: &str,
+        kind: &str,
+        matches: impl Fn(&R::Object) -> bool,
+    ) -> Result<Restorable<R::Object>> {
+        let entries = self.entries.lock().unwrap();
+        entries
+            .iter()
+            .filter_map(|entry| entry.as_any().downcast_ref::<Entry<R>>())
+            .filter_map(|entry| entry.slot.upgrade())
+            .find(|slot| matches(&slot.object.read().unwrap()))
+            .map(|slot| Restorable { slot })
+            .ok_or_else(|| Error::NotRestorable {
+                label: label.to_string(),
+                message: format!("its {kind} was not created through the GpuContext"),
+            })
+    }
+}
+
+pub(crate) struct BufferRecipe {
+    label: Option<String>,
+    contents: Vec<u8>,
+    usage: wgpu::BufferUsages,
+}
+
+impl BufferRecipe {
+    pub(crate) fn new(desc: &wgpu::util::BufferInitDescriptor) -> Self {
+        Self {
+            label: desc.label.map(ToOwned::to_owned),
+            contents: desc.contents.to_vec(),
+            usage: desc.usage,
+        }
+    }
+}
+
+impl Recipe for BufferRecipe {
+    type Object = TrackedBuffer;
+
+    fn create(&self, ctx: &GpuContext) -> TrackedBuffer {
+        use wgpu::util::DeviceExt;
+
+        let label = self.label.as_deref();
+        let buffer = ctx
+            .device
+            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label,
+                contents: &self.contents,
+                usage: self.usage,
+            });
+        TrackedBuffer::new(buffer, &ctx.memory, label.unwrap_or("unnamed"))
+    }
+}
+
+pub(crate) struct TextureRecipe {
+    width: u32,
+    height: u32,
+    label: Option<String>,
+    format: wgpu::TextureFormat,
+    usage: wgpu::TextureUsages,
+    mip_level_count: u32,
+    sample_count: u32,
+    sampler: wgpu::SamplerDescriptor<'static>,
+    /// RGBA8 pixels of the first mip level.
+    pixels: Option<Vec<u8>>,
+}
+
+impl TextureRecipe {
+    pub(crate) fn new(
+        width: u32,
+        height: u32,
+        options: &TextureOptions,
+        pixels: Option<Vec<u8>>,
+    ) -> Self {
+        let sampler = &options.sampler;
+        Self {
+            width,
+            height,
+            label: options.label.map(ToOwned::to_owned),
+            format: options.format,
+            usage: options.usage,
+            mip_level_count: options.mip_level_count,
+            sample_count: options.sample_count,
+            // `Texture::new` labels the sampler like the texture.
+            sampler: wgpu::SamplerDescriptor {
+                label: None,
+                address_mode_u: sampler.address_mode_u,
+                address_mode_v: sampler.address_mode_v,
+                address_mode_w: sampler.address_mode_w,
+                mag_filter: sampler.mag_filter,
+                min_filter: sampler.min_filter,
+                mipmap_filter: sampler.mipmap_filter,
+                lod_min_clamp: sampler.lod_min_clamp,
+                lod_max_clamp: sampler.lod_max_clamp,
+                compare: sampler.compare,
+                anisotropy_clamp: sampler.anisotropy_clamp,
+                border_color: sampler.border_color,
+            },
+            pixels,
+        }
+    }
+}
+
+impl Recipe for TextureRecipe {
+    type Object = Texture;
+
+    fn create(&self, ctx: &GpuContext) -> Texture {
+        let label = self.label.as_deref();
+        let options = TextureOptions {
+            label,
+            format: self.format,
+            usage: self.usage,
+            mip_level_count: self.mip_level_count,
+            sample_count: self.sample_count,
+            sampler: self.sampler.clone(),
+        };
+        let texture = Texture::new(&ctx.device, self.width, self.height, &options);
+        if let Some(pixels) = &self.pixels {
+            texture.write_rgba(&ctx.queue, pixels);
+        }
+        texture.track(&ctx.memory, label.unwrap_or("unnamed"))
+    }
+}
+
+pub(crate) struct ShaderModuleRecipe {
+    label: Option<String>,
+    source: String,
+}
+
+impl ShaderModuleRecipe {
+    pub(crate) fn new(desc: &wgpu::ShaderModuleDescriptor) -> Result<Self> {
+        let label = desc.label.map(ToOwned::to_owned);
+        match &desc.source {
+            wgpu::ShaderSource::Wgsl(source) => Ok(Self {
+                label,
+                source: source.to_string(),
+            }),
+            _ => Err(Error::NotRestorable {
+                label: label.unwrap_or_else(|| "unnamed".to_string()),
+                message: "only WGSL sources are kept".to_string(),
+            }),
+        }
+    }
+}
+
+impl Recipe for ShaderModuleRecipe {
+    type Object = wgpu::ShaderModule;
+
+    fn create(&self, ctx: &GpuContext) -> wgpu::ShaderModule {
+        ctx.device
+            .create_shader_module(wgpu::ShaderModuleDescriptor {
+                label: self.label.as_deref(),
+                source: wgpu::ShaderSource::Wgsl(self.source.as_str().into()),
+            })
+    }
+}
+
+pub(crate) struct BindGroupLayoutRecipe {
+    label: Option<String>,
+    entries: Vec<wgpu::BindGroupLayoutEntry>,
+}
+
+impl BindGroupLayoutRecipe {
+    pub(crate) fn new(desc: &wgpu::BindGroupLayoutDescriptor) -> Self {
+        Self {
+            label: desc.label.map(ToOwned::to_owned),
+            entries: desc.entries.to_vec(),
+        }
+    }
+}
+
+impl Recipe for BindGroupLayoutRecipe {
+    type Object = wgpu::BindGroupLayout;
+
+    fn create(&self, ctx: &GpuContext) -> wgpu::BindGroupLayout {
+        ctx.device
+            .create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                label: self.label.as_deref(),
+                entries: &self.entries,
+            })
+    }
+}
+
+pub(crate) struct PipelineLayoutRecipe {
+    label: Option<String>,
+    bind_group_layouts: Vec<Restorable<wgpu::BindGroupLayout>>,
+    push_constant_ranges: Vec<wgpu::PushConstantRange>,
+}
+
+impl PipelineLayoutRecipe {
+    pub(crate) fn new(registry: &Registry, desc: &wgpu::PipelineLayoutDescriptor) -> Result<Self> {
+        let label = desc.label.unwrap_or("unnamed");
+        let bind_group_layouts = desc
+            .bind_group_layouts
+            .iter()
+            .map(|layout| {
+                registry.find::<BindGroupLayoutRecipe>(label, "bind group layout", |created| {
+                    created.global_id() == layout.global_id()
+                })
+            })
+            .collect::<Result<_>>()?;
+        Ok(Self {
+            label: desc.label.map(ToOwned::to_owned),
+            bind_group_layouts,
+            push_constant_ranges: desc.push_constant_ranges.to_vec(),
+        })
+    }
+}
+
+impl Recipe for PipelineLayoutRecipe {
+    type Object = wgpu::PipelineLayout;
+
+    fn create(&self, ctx: &GpuContext) -> wgpu::PipelineLayout {
+        let layouts = self
+            .bind_group_layouts
+            .iter()
+            .map(Restorable::get)
+            .collect::<Vec<_>>();
+        let layouts = layouts.iter().map(AsRef::as_ref).collect::<Vec<_>>();
+        ctx.device
+            .create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: self.label.as_deref(),
+                bind_group_layouts: &layouts,
+                push_constant_ranges: &self.push_constant_ranges,
+            })
+    }
+}
+
+/// What a bind group entry binds, from a buffer or a [`Texture`] created through the context.
+enum Bound {
+    Buffer {
+        buffer: Restorable<TrackedBuffer>,
+        offset: wgpu::BufferAddress,
+        size: Option<wgpu::BufferSize>,
+    },
+    TextureView(Restorable<Texture>),
+    Sampler(Restorable<Texture>),
+}
+
+/// A `Bound` on the current device.
+enum Resolved {
+    Buffer {
+        buffer: Arc<TrackedBuffer>,
+        offset: wgpu::BufferAddress,
+        size: Option<wgpu::BufferSize>,
+    },
+    TextureView(Arc<Texture>),
+    Sampler(Arc<Texture>),
+}
+
+impl Bound {
+    fn resolve(&self) -> Resolved {
+        match self {
+            Self::Buffer {
+                buffer,
+                offset,
+                size,
+            } => Resolved::Buffer {
+                buffer: buffer.get(),
+                offset: *offset,
+                size: *size,
+            },
+            Self::TextureView(texture) => Resolved::TextureView(texture.get()),
+            Self::Sampler(texture) => Resolved::Sampler(texture.get()),
+        }
+    }
+}
+
+impl Resolved {
+    fn binding_resource(&self) -> wgpu::BindingResource<'_> {
+        match self {
+            Self::Buffer {
+                buffer,
+                offset,
+                size,
+            } => wgpu::BindingResource::Buffer(wgpu::BufferBinding {
+                buffer,
+                offset: *offset,
+                size: *size,
+            }),
+            Self::TextureView(texture) => wgpu::BindingResource::TextureView(&texture.view),
+            Self::Sampler(texture) => wgpu::BindingResource::Sampler(&texture.sampler),
+        }
+    }
+}
+
+pub(crate) struct BindGroupRecipe {
+    label: Option<String>,
+    layout: Restorable<wgpu::BindGroupLayout>,
+    entries: Vec<(u32, Bound)>,
+}
+
+impl BindGroupRecipe {
+    pub(crate) fn new(registry: &Registry, desc: &wgpu::BindGroupDescriptor) -> Result<Self> {
+        let label = desc.label.unwrap_or("unnamed");
+        let layout = registry.find::<BindGroupLayoutRecipe>(label, "layout", |created| {
+            created.global_id() == desc.layout.global_id()
+        })?;
+        let entries = desc
+            .entries
+            .iter()
+            .map(|entry| {
+                let bound = match &entry.resource {
+                    wgpu::BindingResource::Buffer(binding) => Bound::Buffer {
+                        buffer: registry.find::<BufferRecipe>(label, "buffer", |created| {
+                            created.global_id() == binding.buffer.global_id()
+                        })?,
+                        offset: binding.offset,
+                        size: binding.size,
+                    },
+                    wgpu::BindingResource::TextureView(view) => Bound::TextureView(
+                        registry.find::<TextureRecipe>(label, "texture", |created| {
+                            created.view.global_id() == view.global_id()
+                        })?,
+                    ),
+                    wgpu::BindingResource::Sampler(sampler) => Bound::Sampler(
+                        registry.find::<TextureRecipe>(label, "sampler", |created| {
+                            created.sampler.global_id() == sampler.global_id()
+                        })?,
+                    ),
+                    _ => {
+                        return Err(Error::NotRestorable {
+                            label: label.to_string(),
+                            message: "binding arrays are not kept".to_string(),
+                        })
+                    }
+                };
+                Ok((entry.binding, bound))
+            })
+            .collect::<Result<_>>()?;
+        Ok(Self {
+            label: desc.label.map(ToOwned::to_owned),
+            layout,
+            entries,
+        })
+    }
+}
+
+impl Recipe for BindGroupRecipe {
+    type Object = wgpu::BindGroup;
+
+    fn create(&self, ctx: &GpuContext) -> wgpu::BindGroup {
+        let layout = self.layout.get();
+        let resolved = self
+            .entries
+            .iter()
+            .map(|(binding, bound)| (*binding, bound.resolve()))
+            .collect::<Vec<_>>();
+        let entries = resolved
+            .iter()
+            .map(|(binding, resolved)| wgpu::BindGroupEntry {
+                binding: *binding,
+                resource: resolved.binding_resource(),
+            })
+            .collect::<Vec<_>>();
+        ctx.device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: self.label.as_deref(),
+            layout: &layout,
+            entries: &entries,
+        })
+    }
+}
+
+/// A shader stage of a render pipeline.
+struct StageRecipe {
+    module: Restorable<wgpu::ShaderModule>,
+    entry_point: String,
+    constants: HashMap<String, f64>,
+    zero_initialize_workgroup_memory: bool,
+}
+
+impl StageRecipe {
+    fn new(
+        registry: &Registry,
+        label: &str,
+        module: &wgpu::ShaderModule,
+        entry_point: &str,
+        options: &wgpu::PipelineCompilationOptions,
+    ) -> Result<Self> {
+        Ok(Self {
+            module: registry.find::<ShaderModuleRecipe>(label, "shader module", |created| {
+                created.global_id() == module.global_id()
+            })?,
+            entry_point: entry_point.to_string(),
+            constants: options.constants.clone(),
+            zero_initialize_workgroup_memory: options.zero_initialize_workgroup_memory,
+        })
+    }
+
+    fn compilation_options(&self) -> wgpu::PipelineCompilationOptions<'_> {
+        wgpu::PipelineCompilationOptions {
+            constants: &self.constants,
+            zero_initialize_workgroup_memory: self.zero_initialize_workgroup_memory,
+        }
+    }
+}
+
+struct VertexBufferRecipe {
+    array_stride: wgpu::BufferAddress,
+    step_mode: wgpu::VertexStepMode,
+    attributes: Vec<wgpu::VertexAttribute>,
+}
+
+pub(crate) struct RenderPipelineRecipe {
+    label: Option<String>,
+    layout: Option<Restorable<wgpu::PipelineLayout>>,
+    vertex: StageRecipe,
+    buffers: Vec<VertexBufferRecipe>,
+    fragment: Option<(StageRecipe, Vec<Option<wgpu::ColorTargetState>>)>,
+    primitive: wgpu::PrimitiveState,
+    depth_stencil: Option<wgpu::DepthStencilState>,
+    multisample: wgpu::MultisampleState,
+    multiview: Option<NonZeroU32>,
+}
+
+impl RenderPipelineRecipe {
+    pub(crate) fn new(registry: &Registry, desc: &wgpu::RenderPipelineDescriptor) -> Result<Self> {
+        let label = desc.label.unwrap_or("unnamed");
+        let layout = desc
+            .layout
+            .map(|layout| {
+                registry.find::<PipelineLayoutRecipe>(label, "layout", |created| {
+                    created.global_id() == layout.global_id()
+                })
+            })
+            .transpose()?;
+        let vertex = StageRecipe::new(
+            registry,
+            label,
+            desc.vertex.module,
+            desc.vertex.entry_point,
+            &desc.vertex.compilation_options,
+        )?;
+        let fragment = desc
+            .fragment
+            .as_ref()
+            .map(|fragment| -> Result<_> {
+                let stage = StageRecipe::new(
+                    registry,
+                    label,
+                    fragment.module,
+                    fragment.entry_point,
+                    &fragment.compilation_options,
+                )?;
+                Ok((stage, fragment.targets.to_vec()))
+            })
+            .transpose()?;
+        Ok(Self {
+            label: desc.label.map(ToOwned::to_owned),
+            layout,
+            vertex,
+            buffers: desc
+                .vertex
+                .buffers
+                .iter()
+                .map(|buffer| VertexBufferRecipe {
+                    array_stride: buffer.array_stride,
+                    step_mode: buffer.step_mode,
+                    attributes: buffer.attributes.to_vec(),
+                })
+                .collect(),
+            fragment,
+            primitive: desc.primitive,
+            depth_stencil: desc.depth_stencil.clone(),
+            multisample: desc.multisample,
+            multiview: desc.multiview,
+        })
+    }
+}
+
+impl Recipe for RenderPipelineRecipe {
+    type Object = wgpu::RenderPipeline;
+
+    fn create(&self, ctx: &GpuContext) -> wgpu::RenderPipeline {
+        let layout = self.layout.as_ref().map(Restorable::get);
+        let vertex_module = self.vertex.module.get();
+        let fragment_module = self.fragment.as_ref().map(|(stage, _)| stage.module.get());
+        let buffers = self
+            .buffers
+            .iter()
+            .map(|buffer| wgpu::VertexBufferLayout {
+                array_stride: buffer.array_stride,
+                step_mode: buffer.step_mode,
+                attributes: &buffer.attributes,
+            })
+            .collect::<Vec<_>>();
+
+        ctx.device
+            .create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                label: self.label.as_deref(),
+                layout: layout.as_deref(),
+                vertex: wgpu::VertexState {
+                    module: &vertex_module,
+                    entry_point: &self.vertex.entry_point,
+                    buffers: &buffers,
+                    compilation_options: self.vertex.compilation_options(),
+                },
+                fragment: self.fragment.as_ref().zip(fragment_module.as_deref()).map(
+                    |((stage, targets), module)| wgpu::FragmentState {
+                        module,
+                        entry_point: &stage.entry_point,
+                        targets,
+                        compilation_options: stage.compilation_options(),
+                    },
+                ),
+                primitive: self.primitive,
+                depth_stencil: self.depth_stencil.clone(),
+                multisample: self.multisample,
+                multiview: self.multiview,
+            })
+    }
+}
@@ -0,0 +1,96 @@
+use crate::{Error, InputEvent, Result};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::time::Duration;
+
+/// An input event and the update it was seen before.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TimedInput {
+    /// How many updates had run when the event arrived. Replayed, it is applied right before the
+    /// update with this index.
+    pub frame: u64,
+    pub event: InputEvent,
+}
+
+/// A stream of input events, indexed by fixed-rate update, to reproduce a session.
+///
+/// Since the app's updates only see [`GpuContext::input`](crate::GpuContext::input), replaying
+/// the events at the same update indexes drives the simulation through exactly the same states,
+/// however fast frames are rendered. Events handled directly by
+/// [`Application::input`](crate::Application::input) are not replayed.
+///
+/// Stored as RON, see [`State::record_input`](crate::State::record_input) and
+/// [`State::replay_input`](crate::State::replay_input).
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct InputRecording {
+    /// The fixed timestep of the app that was recorded. Replaying with another changes the
+    /// outcome.
+    pub timestep: Duration,
+    pub events: Vec<TimedInput>,
+}
+
+impl InputRecording {
+    pub fn new(timestep: Duration) -> Self {
+        Self {
+            timestep,
+            events: Vec::new(),
+        }
+    }
+
+    /// Adds `event`, to be applied before the update with index `frame`. Events are kept in
+    /// order of `frame`, and in the order they were pushed within a frame.
+    pub fn push(&mut self, frame: u64, event: InputEvent) {
+        let index = self.events.partition_point(|input| input.frame <= frame);
+        self.events.insert(index, TimedInput { frame, event });
+    }
+
+    /// The number of updates the recording spans, up to the last event.
+    pub fn frame_count(&self) -> u64 {
+        self.events.last().map_or(0, |input| input.frame + 1)
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let text = std::fs::read_to_string(path).map_err(|e| Error::read(path, e))?;
+        ron::from_str(&text).map_err(|e| Error::read(path, e))
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path
+            .parent()
+            .filter(|parent| !parent.as_os_str().is_empty())
+        {
+            std::fs::create_dir_all(parent).map_err(|e| Error::write(parent, e))?;
+        }
+        let text = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+            .map_err(|e| Error::write(path, e))?;
+        std::fs::write(path, text).map_err(|e| Error::write(path, e))
+    }
+}
+
+/// Feeds an [`InputRecording`] to the input state, update by update.
+pub(crate) struct Replay {
+    recording: InputRecording,
+    /// The first event not applied yet.
+    next: usize,
+}
+
+impl Replay {
+    pub(crate) fn new(recording: InputRecording) -> Self {
+        Self { recording, next: 0 }
+    }
+
+    /// The events to apply before the update with index `frame`, and the ones before it that
+    /// were missed.
+    pub(crate) fn due(&mut self, frame: u64) -> &[TimedInput] {
+        let start = self.next;
+        let events = &self.recording.events;
+        while self.next < events.len() && events[self.next].frame <= frame {
+            self.next += 1;
+        }
+        &events[start..self.next]
+    }
+
+    pub(crate) fn is_done(&self) -> bool {
+        self.next >= self.recording.events.len()
+    }
+}
@@ -0,0 +1,516 @@
+use crate::context::WindowRequest;
+use crate::{
+    action, print_adapters, save_screenshot, Application, Error, InputRecording, Recorder,
+    RedrawPolicy, RendererConfig, State,
+};
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use winit::application::ApplicationHandler;
+use winit::dpi::PhysicalSize;
+use winit::event::{StartCause, WindowEvent};
+use winit::event_loop::{ActiveEventLoop, ControlFlow, EventLoop};
+use winit::window::WindowId;
+
+/// How often the runner checks whether a hot-reloaded shader changed, when frames are only
+/// rendered on demand.
+const HOT_RELOAD_INTERVAL: Duration = Duration::from_millis(200);
+
+struct Runner<A: Application> {
+    config: RendererConfig,
+    state: Option<State<A>>,
+    scale_factors: HashMap<WindowId, f64>,
+    /// Windows whose next frame is saved as a screenshot.
+    screenshots: HashSet<WindowId>,
+    /// Records the first window until the recording set in the config is complete.
+    recorder: Option<Recorder>,
+    last_update: Instant,
+    last_report: Instant,
+    /// Whether the last frame requested no other, so the app waited for input on demand.
+    idle: bool,
+    /// The error that stopped the event loop, returned from `run`.
+    error: Option<Error>,
+}
+
+impl<A: Application> Runner<A> {
+    fn new(config: RendererConfig) -> Self {
+        Self {
+            config,
+            state: None,
+            scale_factors: HashMap::new(),
+            screenshots: HashSet::new(),
+            recorder: None,
+            last_update: Instant::now(),
+            last_report: Instant::now(),
+            idle: false,
+            error: None,
+        }
+    }
+
+    fn fail(&mut self, event_loop: &ActiveEventLoop, error: Error) {
+        eprintln!("{error}");
+        self.error = Some(error);
+        self.exit(event_loop);
+    }
+
+    fn request_redraw(&self) {
+        if let Some(state) = self.state.as_ref() {
+            state.windows().for_each(|window| window.request_redraw());
+        }
+    }
+
+    /// Opens and closes the windows the app asked for through its
+    /// [`GpuContext`](crate::GpuContext).
+    fn handle_window_requests(&mut self, event_loop: &ActiveEventLoop) {
+        let Some(requests) = self
+            .state
+            .as_ref()
+            .map(|state| state.context().take_window_requests())
+        else {
+            return;
+        };
+        for request in requests {
+            let Some(state) = self.state.as_mut() else {
+                return;
+            };
+            match request {
+                WindowRequest::Open(attributes) => {
+                    let window = match event_loop.create_window(*attributes) {
+                        Ok(window) => Arc::new(window),
+                        Err(e) => {
+                            eprintln!("{}", Error::from(e));
+                            continue;
+                        }
+                    };
+                    let id = window.id();
+                    let scale_factor = window.scale_factor();
+                    match state.add_window(Arc::clone(&window)) {
+                        Ok(()) => {
+                            println!("Open Window {id:?} !");
+                            self.scale_factors.insert(id, scale_factor);
+                            window.request_redraw();
+                        }
+                        Err(e) => eprintln!("{e}"),
+                    }
+                }
+                WindowRequest::Close(id) => self.close_window(event_loop, id),
+            }
+        }
+    }
+
+    /// Tears down the surface of one window. The app exits with its last window.
+    fn close_window(&mut self, event_loop: &ActiveEventLoop, id: WindowId) {
+        let Some(state) = self.state.as_mut() else {
+            return;
+        };
+        if state.remove_window(id) {
+            println!("Close Window {id:?} !");
+            self.scale_factors.remove(&id);
+        }
+        if state.windows().next().is_none() {
+            self.exit(event_loop);
+        }
+    }
+
+    /// Reports the frame statistics once the interval set by [`Application::stats_report`] has
+    /// passed since the last report.
+    fn report_stats(&mut self) {
+        let report = A::stats_report();
+        if !report.is_enabled() || self.last_report.elapsed() < report.interval {
+            return;
+        }
+        let Some(state) = self.state.as_ref() else {
+            return;
+        };
+        self.last_report = Instant::now();
+
+        let stats = state.stats();
+        if report.log {
+            log::info!(
+                "{stats} | {} | GPU memory {}",
+                state.draw_counts(),
+                state.context().memory.total()
+            );
+        }
+        if let (true, Some(window)) = (report.title, state.windows().next()) {
+            window.set_title(&format!(
+                "{} - {:.0} fps ({:.2} ms)",
+                A::window_attributes().title,
+                stats.fps(),
+                stats.frame().average.as_secs_f64() * 1000.0
+            ));
+        }
+    }
+
+    fn exit(&mut self, event_loop: &ActiveEventLoop) {
+        println!("Exit App !");
+
+        if let (Some(path), Some(state)) = (self.config.record_input.as_ref(), self.state.as_mut())
+        {
+            if let Some(recording) = state.take_input_recording() {
+                match recording.save(path) {
+                    Ok(()) => log::info!(
+                        "Recorded the input of {} frames to {}",
+                        state.frame_index(),
+                        path.display()
+                    ),
+                    Err(e) => log::warn!("{e:#}"),
+                }
+            }
+        }
+        if let (Some(path), Some(state)) = (self.config.gpu_trace.as_ref(), self.state.as_mut()) {
+            state.flush_profiler();
+            match state.profiler().write_chrome_trace(path) {
+                Ok(()) => log::info!("GPU trace written to {}", path.display()),
+                Err(e) => log::warn!("Failed to write the GPU trace to {}: {e}", path.display()),
+            }
+        }
+
+        // https://github.com/rust-windowing/winit/issues/3668
+        // Use Option::take to Dispose the windows held by the state
+        self.state.take();
+        event_loop.exit();
+    }
+}
+
+impl<A: Application> ApplicationHandler for Runner<A> {
+    fn resumed(&mut self, event_loop: &ActiveEventLoop) {
+        println!("App Resumed !");
+
+        // Initialized at first Resumed Event
+        if self.state.is_none() {
+            println!("Create Window !");
+            let window = match event_loop.create_window(A::window_attributes()) {
+                Ok(window) => Arc::new(window),
+                Err(e) => return self.fail(event_loop, e.into()),
+            };
+            let mut state =
+                match pollster::block_on(State::<A>::new(Arc::clone(&window), &self.config)) {
+                    Ok(state) => state,
+                    Err(e) => return self.fail(event_loop, e),
+                };
+            println!("Bind Window !");
+
+            self.scale_factors
+                .insert(window.id(), window.scale_factor());
+            start_input(&mut state, &self.config);
+            self.state = Some(state);
+            self.recorder = match self.config.record.clone().map(Recorder::new).transpose() {
+                Ok(recorder) => recorder,
+                Err(e) => return self.fail(event_loop, e),
+            };
+            self.last_update = Instant::now();
+            self.last_report = Instant::now();
+            window.pre_present_notify();
+            window.request_redraw();
+        } else if let Some(state) = self.state.as_mut() {
+            // Back from `suspended`: the windows survived, their surfaces are created again.
+            if let Err(e) = state.resume() {
+                return self.fail(event_loop, e);
+            }
+            // Don't catch up on the updates missed while in the background.
+            self.last_update = Instant::now();
+            self.request_redraw();
+        }
+    }
+
+    fn new_events(&mut self, _event_loop: &ActiveEventLoop, cause: StartCause) {
+        // The deadline set by `RedrawPolicy::Capped` was reached. With `OnDemand`, it is only
+        // the next check of the shader watchers.
+        if let (StartCause::ResumeTimeReached { .. }, RedrawPolicy::Capped(_)) =
+            (cause, A::redraw_policy())
+        {
+            self.request_redraw();
+        }
+    }
+
+    fn about_to_wait(&mut self, event_loop: &ActiveEventLoop) {
+        self.handle_window_requests(event_loop);
+        if let Some(state) = self.state.as_mut() {
+            state.reload_shaders();
+            // A lost device is replaced before the next frame.
+            if state.context().take_redraw_request() || state.context().is_device_lost() {
+                self.request_redraw();
+            }
+        }
+        // Shader watchers can't wake the event loop; check them regularly.
+        if self.config.hot_reload && A::redraw_policy() == RedrawPolicy::OnDemand {
+            let next_check = Instant::now() + HOT_RELOAD_INTERVAL;
+            event_loop.set_control_flow(ControlFlow::WaitUntil(next_check));
+        }
+    }
+
+    fn suspended(&mut self, _event_loop: &ActiveEventLoop) {
+        println!("App Suspended !");
+
+        // Surfaces must not outlive the native windows, which some platforms destroy now.
+        if let Some(state) = self.state.as_mut() {
+            state.suspend();
+        }
+    }
+
+    fn window_event(
+        &mut self,
+        event_loop: &ActiveEventLoop,
+        window_id: WindowId,
+        event: WindowEvent,
+    ) {
+        let Some(state) = self.state.as_mut() else {
+            return;
+        };
+        // Events can still arrive for a window that was just closed.
+        let Some(window) = state.window(window_id).cloned() else {
+            return;
+        };
+        if state.window_input(window_id, &event) {
+            window.request_redraw();
+            return;
+        }
+        // println!("WindowEvent: {event:?}");
+        let bindings = state.context().input.bindings();
+        let quit = bindings.is_triggered(action::QUIT, &event);
+        let screenshot = bindings.is_triggered(action::SCREENSHOT, &event);
+        match event {
+            WindowEvent::CloseRequested => self.close_window(event_loop, window_id),
+            _ if quit => self.exit(event_loop),
+            _ if screenshot => {
+                self.screenshots.insert(window_id);
+                window.request_redraw();
+            }
+            WindowEvent::RedrawRequested => {
+                if state.context().is_device_lost() {
+                    if let Err(e) = pollster::block_on(state.recover()) {
+                        return self.fail(event_loop, e);
+                    }
+                }
+
+                // While recording, the first window's frames advance the simulation by the
+                // recording's frame time, and the others don't advance it at all.
+                let first_window = state.windows().next().map(|w| w.id()) == Some(window_id);
+
+                // With several windows, only the first redraw after some time has passed runs
+                // the updates; the others render the same state.
+                let now = Instant::now();
+                match self.recorder.as_ref() {
+                    Some(recorder) if first_window => state.simulate(match recorder.recorded() {
+                        0 => recorder.recording().start_time(),
+                        _ => recorder.recording().frame_time(),
+                    }),
+                    Some(_) => {}
+                    // Waking up on demand runs at most one update, not the whole time spent idle.
+                    None if self.idle => state.advance((now - self.last_update).min(A::timestep())),
+                    None => state.advance(now - self.last_update),
+                }
+                self.last_update = now;
+
+                let rendered = if self.recorder.is_some() && first_window {
+                    record_frame(state, window_id, &mut self.recorder);
+                    Ok(())
+                } else if self.screenshots.remove(&window_id) {
+                    take_screenshot(state, window_id, &self.config.screenshot_dir);
+                    Ok(())
+                } else {
+                    state.render_window(window_id)
+                };
+                let driven = self.recorder.is_some() || state.is_replaying();
+                match rendered {
+                    Ok(_) => {}
+                    Err(e) => {
+                        eprintln!("{:?}", e);
+                        match e {
+                            Error::Surface(wgpu::SurfaceError::Lost) => {
+                                state.resize_window(window_id, window.inner_size())
+                            }
+                            // The frame is skipped, the next one gets a new surface texture.
+                            Error::Surface(
+                                wgpu::SurfaceError::Timeout | wgpu::SurfaceError::Outdated,
+                            ) => (),
+                            _ => self.fail(event_loop, e),
+                        }
+                    }
+                }
+                self.report_stats();
+
+                let (control_flow, redraw) = after_frame(A::redraw_policy(), now, driven);
+                event_loop.set_control_flow(control_flow);
+                if redraw {
+                    window.request_redraw();
+                }
+                self.idle = A::redraw_policy() == RedrawPolicy::OnDemand && !redraw;
+            }
+            WindowEvent::Resized(physical_size) => {
+                println!("On Resized !");
+                state.resize_window(window_id, physical_size);
+                window.request_redraw();
+            }
+            WindowEvent::ScaleFactorChanged {
+                scale_factor,
+                mut inner_size_writer,
+            } => {
+                let old_scale_factor = self
+                    .scale_factors
+                    .insert(window_id, scale_factor)
+                    .unwrap_or(1.0);
+                let PhysicalSize { width, height } = window.inner_size();
+                let new_width = width as f64 / old_scale_factor * scale_factor;
+                let new_height = height as f64 / old_scale_factor * scale_factor;
+                let new_inner_size =
+                    PhysicalSize::new(new_width.floor() as u32, new_height.floor() as u32);
+                let _ = inner_size_writer.request_inner_size(new_inner_size);
+                println!("Request new size: {new_inner_size:?}");
+            }
+            _ => (),
+        }
+    }
+}
+
+/// Renders the next frame of `window_id` into the recorder, and completes the recording after
+/// its last frame or when it fails, leaving `recorder` empty.
+fn record_frame<A: Application>(
+    state: &mut State<A>,
+    window_id: WindowId,
+    recorder: &mut Option<Recorder>,
+) {
+    let Some(active) = recorder.as_mut() else {
+        return;
+    };
+    let recorded = state
+        .render_window_to_image(window_id)
+        .and_then(|image| active.push(&image));
+    if let Err(e) = recorded {
+        log::warn!("Recording stopped: {e:#}");
+        *recorder = None;
+        return;
+    }
+    if !active.is_done() {
+        return;
+    }
+
+    let finished = recorder.take().unwrap();
+    let frames = finished.recorded();
+    match finished.finish() {
+        Ok(path) => log::info!("Recorded {frames} frames to {}", path.display()),
+        Err(e) => log::warn!("{e:#}"),
+    }
+}
+
+/// Renders the next frame of `window_id` into an image and saves it as a PNG in `dir`. The file
+/// is written on another thread, so the frame isn't held up.
+fn take_screenshot<A: Application>(state: &mut State<A>, window_id: WindowId, dir: &Path) {
+    let image = match state.render_window_to_image(window_id) {
+        Ok(image) => image,
+        Err(e) => return log::warn!("Failed to capture a screenshot: {e:#}"),
+    };
+    let dir = dir.to_path_buf();
+    std::thread::spawn(move || match save_screenshot(&image, &dir) {
+        Ok(path) => log::info!("Screenshot saved to {}", path.display()),
+        Err(e) => log::warn!("{e:#}"),
+    });
+}
+
+/// Starts the input replay or recording asked for in `config`.
+fn start_input<A: Application>(state: &mut State<A>, config: &RendererConfig) {
+    if let Some(path) = &config.replay_input {
+        match InputRecording::load(path) {
+            Ok(recording) => {
+                log::info!(
+                    "Replaying {} input events over {} frames",
+                    recording.events.len(),
+                    recording.frame_count()
+                );
+                state.replay_input(recording);
+            }
+            Err(e) => log::error!("{e:#}, using the live input"),
+        }
+    }
+    if config.record_input.is_some() {
+        state.record_input();
+    }
+}
+
+/// How the event loop goes on after a frame rendered at `now`, and whether the window asks for
+/// the next frame right away. Replays and recordings (`driven`) need every frame, even from
+/// apps that otherwise only render on demand.
+fn after_frame(policy: RedrawPolicy, now: Instant, driven: bool) -> (ControlFlow, bool) {
+    match policy {
+        RedrawPolicy::Continuous => (ControlFlow::Poll, true),
+        RedrawPolicy::OnDemand => (ControlFlow::Wait, driven),
+        RedrawPolicy::Capped(fps) => {
+            let next_frame = now + Duration::from_secs_f64(1.0 / fps);
+            (ControlFlow::WaitUntil(next_frame), false)
+        }
+    }
+}
+
+/// Opens a window and runs `A` in it until its windows are closed, or until an [`Error`] stops
+/// it. The app can open more windows with
+/// [`GpuContext::open_window`](crate::GpuContext::open_window).
+///
+/// The renderer is configured by [`Application::renderer_config`], with environment variables
+/// and command-line arguments applied on top. Set `RUST_LOG` to change what is logged.
+pub fn run<A: Application>() -> Result<(), Error> {
+    let _ = env_logger::Builder::from_env(
+        env_logger::Env::default().default_filter_or("warn,wgpu_template=info"),
+    )
+    .try_init();
+
+    let config = A::renderer_config().with_overrides();
+    if std::env::args().any(|arg| arg == "--list-adapters") {
+        print_adapters(wgpu::Backends::all(), &config.adapter);
+        return Ok(());
+    }
+    if std::env::args().any(|arg| arg == "--list-bindings") {
+        print!("{}", config.bindings(A::bindings()));
+        return Ok(());
+    }
+
+    let mut runner = Runner::<A>::new(config);
+    let event_loop = EventLoop::new()?;
+
+    event_loop.set_control_flow(match A::redraw_policy() {
+        RedrawPolicy::Continuous => ControlFlow::Poll,
+        RedrawPolicy::OnDemand | RedrawPolicy::Capped(_) => ControlFlow::Wait,
+    });
+    event_loop.run_app(&mut runner)?;
+
+    match runner.error.take() {
+        Some(error) => Err(error),
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn each_policy_picks_its_control_flow() {
+        let now = Instant::now();
+        for driven in [false, true] {
+            assert_eq!(
+                after_frame(RedrawPolicy::Continuous, now, driven),
+                (ControlFlow::Poll, true)
+            );
+            assert_eq!(
+                after_frame(RedrawPolicy::Capped(50.0), now, driven),
+                (
+                    ControlFlow::WaitUntil(now + Duration::from_millis(20)),
+                    false
+                )
+            );
+        }
+        assert_eq!(
+            after_frame(RedrawPolicy::OnDemand, now, false),
+            (ControlFlow::Wait, false)
+        );
+    }
+
+    #[test]
+    fn replays_and_recordings_keep_on_demand_apps_rendering() {
+        assert_eq!(
+            after_frame(RedrawPolicy::OnDemand, Instant::now(), true),
+            (ControlFlow::Wait, true)
+        );
+    }
+}
@@ -0,0 +1,19 @@
+use crate::{Error, Result};
+use std::path::{Path, PathBuf};
+
+/// Where a screenshot taken now is saved: `dir/screenshot-<local date and time>.png`.
+pub fn screenshot_path(dir: &Path) -> PathBuf {
+    let timestamp = chrono::Local::now().format("%Y-%m-%d_%H-%M-%S%.3f");
+    dir.join(format!("screenshot-{timestamp}.png"))
+}
+
+/// Saves `image` as a PNG in `dir`, creating the directory if needed, and returns the path of
+/// the file.
+pub fn save_screenshot(image: &image::RgbaImage, dir: &Path) -> Result<PathBuf> {
+    std::fs::create_dir_all(dir).map_err(|e| Error::write(dir, e))?;
+    let path = screenshot_path(dir);
+    image
+        .save_with_format(&path, image::ImageFormat::Png)
+        .map_err(|e| Error::write(&path, e))?;
+    Ok(path)
+}
@@ -0,0 +1,252 @@
+use crate::{Error, GpuContext, Restorable, Result};
+use notify::{RecursiveMode, Watcher};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+/// A WGSL file compiled into the binary, and where it is on disk for hot reloading. Made by
+/// [`hot_wgsl!`](crate::hot_wgsl).
+#[derive(Clone, Copy, Debug)]
+pub struct ShaderFile {
+    pub path: &'static str,
+    pub source: &'static str,
+}
+
+/// Embeds a WGSL file like `include_str!` does, remembering its path so that a [`HotShader`]
+/// can read it from disk instead. The path is relative to the file the macro is used in.
+#[macro_export]
+macro_rules! hot_wgsl {
+    ($file:literal) => {
+        $crate::ShaderFile {
+            path: concat!(env!("CARGO_MANIFEST_DIR"), "/", file!(), "/../", $file),
+            source: include_str!($file),
+        }
+    };
+}
+
+/// A shader module that follows its WGSL file while [`GpuContext::hot_reload`] is on.
+///
+/// With hot reloading, the source is read from disk and the file is watched; after it was saved,
+/// [`reload`](Self::reload) compiles it again. A source that does not compile is reported, with
+/// its line and column, and the previous module is kept, so the app keeps running with the last
+/// good shader. Without hot reloading, the source embedded in the binary is used and `reload`
+/// never returns anything.
+pub struct HotShader {
+    label: String,
+    path: PathBuf,
+    module: Restorable<wgpu::ShaderModule>,
+    /// Why the latest version of the file did not compile.
+    error: Option<Error>,
+    /// Watches the file's directory while hot reloading.
+    watcher: Option<notify::RecommendedWatcher>,
+    /// Set by the watcher when the file changed.
+    changed: Arc<AtomicBool>,
+}
+
+impl HotShader {
+    /// Compiles `file`, read from disk when hot reloading. If the file on disk can't be read or
+    /// compiled, the embedded source is used until it is fixed.
+    pub fn new(ctx: &GpuContext, file: ShaderFile) -> Result<Self> {
+        let path = normalize(Path::new(file.path));
+        let label = path.file_name().map_or_else(
+            || file.path.to_string(),
+            |name| name.to_string_lossy().into(),
+        );
+        let changed = Arc::new(AtomicBool::new(false));
+
+        let mut error = None;
+        let mut module = None;
+        let mut watcher = None;
+        if ctx.hot_reload {
+            match watch(&path, &changed, ctx.shader_change_flag()) {
+                Ok(w) => watcher = Some(w),
+                Err(e) => log::warn!("Not watching {path:?}: {e}"),
+            }
+            match read_and_compile(ctx, &label, &path) {
+                Ok(compiled) => module = Some(compiled),
+                Err(e) => {
+                    log::error!("{e}, using the embedded version");
+                    error = Some(e);
+                }
+            }
+        }
+        let module = match module {
+            Some(module) => module,
+            None => compile(ctx, &label, file.source)?,
+        };
+
+        Ok(Self {
+            label,
+            path,
+            module,
+            error,
+            watcher,
+            changed,
+        })
+    }
+
+    pub fn module(&self) -> Arc<wgpu::ShaderModule> {
+        self.module.get()
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn is_watched(&self) -> bool {
+        self.watcher.is_some()
+    }
+
+    /// Why the file did not compile the last time it was loaded, if it didn't.
+    pub fn error(&self) -> Option<&Error> {
+        self.error.as_ref()
+    }
+
+    /// Compiles the file again if it changed since the last call. Returns the new module if it
+    /// compiled, in which case a redraw is requested and the app rebuilds the pipelines that use
+    /// it. Otherwise the error is logged and kept in [`error`](Self::error), and the current
+    /// module stays. Apps call it from [`Application::reload_shaders`](crate::Application::reload_shaders).
+    pub fn reload(&mut self, ctx: &GpuContext) -> Option<Arc<wgpu::ShaderModule>> {
+        if !self.changed.swap(false, Ordering::Relaxed) {
+            return None;
+        }
+        match read_and_compile(ctx, &self.label, &self.path) {
+            Ok(module) => {
+                log::info!("Reloaded {:?}", self.path);
+                self.module = module;
+                self.error = None;
+                ctx.request_redraw();
+                Some(self.module.get())
+            }
+            Err(e) => {
+                log::error!("{e}, keeping the previous version");
+                self.error = Some(e);
+                None
+            }
+        }
+    }
+
+    /// [`reload`](Self::reload)s, and passes the new module to `build`, which creates what uses
+    /// it, e.g. a pipeline. Returns what `build` made, or `None` when the shader was not reloaded
+    /// or `build` failed, in which case the error is logged and the app keeps what it had.
+    pub fn reload_with<T>(
+        &mut self,
+        ctx: &GpuContext,
+        build: impl FnOnce(&wgpu::ShaderModule) -> Result<T>,
+    ) -> Option<T> {
+        let module = self.reload(ctx)?;
+        match build(&module) {
+            Ok(built) => Some(built),
+            Err(e) => {
+                log::error!("{e}, keeping the previous pipeline");
+                None
+            }
+        }
+    }
+}
+
+fn read_and_compile(
+    ctx: &GpuContext,
+    label: &str,
+    path: &Path,
+) -> Result<Restorable<wgpu::ShaderModule>> {
+    let source = std::fs::read_to_string(path).map_err(|e| Error::asset(label, e))?;
+    compile(ctx, label, &source)
+}
+
+fn compile(ctx: &GpuContext, label: &str, source: &str) -> Result<Restorable<wgpu::ShaderModule>> {
+    ctx.try_create_shader_module(wgpu::ShaderModuleDescriptor {
+        label: Some(label),
+        source: wgpu::ShaderSource::Wgsl(source.into()),
+    })
+}
+
+/// Watches the directory of `path` rather than the file, which editors often replace on save.
+fn watch(
+    path: &Path,
+    changed: &Arc<AtomicBool>,
+    any_changed: Arc<AtomicBool>,
+) -> notify::Result<notify::RecommendedWatcher> {
+    let name = path.file_name().map(ToOwned::to_owned);
+    let changed = Arc::clone(changed);
+    let mut watcher =
+        notify::recommended_watcher(move |event: notify::Result<notify::Event>| match event {
+            Ok(event)
+                if (event.kind.is_modify() || event.kind.is_create())
+                    && event
+                        .paths
+                        .iter()
+                        .any(|path| path.file_name() == name.as_deref()) =>
+            {
+                changed.store(true, Ordering::Relaxed);
+                any_changed.store(true, Ordering::Relaxed);
+            }
+            Ok(_) => {}
+            Err(e) => log::warn!("Shader watcher error: {e}"),
+        })?;
+    let dir = path.parent().unwrap_or(Path::new("."));
+    watcher.watch(dir, RecursiveMode::NonRecursive)?;
+    Ok(watcher)
+}
+
+/// Resolves the `..` left by `hot_wgsl!` without touching the file system.
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            std::path::Component::ParentDir => {
+                normalized.pop();
+            }
+            std::path::Component::CurDir => {}
+            component => normalized.push(component),
+        }
+    }
+    normalized
+}
+
+/// Parses and validates WGSL with naga, to report errors where they are in the source.
+pub(crate) fn check_wgsl(label: &str, source: &str) -> Result<()> {
+    let module = naga::front::wgsl::parse_str(source).map_err(|e| match e.location(source) {
+        Some(location) => Error::ShaderSource {
+            label: label.to_string(),
+            line: location.line_number,
+            column: location.line_position,
+            message: e.message().to_string(),
+        },
+        None => Error::Shader {
+            label: label.to_string(),
+            message: e.message().to_string(),
+        },
+    })?;
+
+    let mut validator = naga::valid::Validator::new(
+        naga::valid::ValidationFlags::all(),
+        naga::valid::Capabilities::all(),
+    );
+    if let Err(e) = validator.validate(&module) {
+        // The error's chain goes from the item that is invalid down to the reason.
+        let mut message = e.as_inner().to_string();
+        let mut source_error = std::error::Error::source(e.as_inner());
+        while let Some(inner) = source_error {
+            message.push_str(&format!(": {inner}"));
+            source_error = inner.source();
+        }
+        // Spans go from the outermost item to the innermost.
+        return Err(match e.spans().last() {
+            Some((span, _)) => {
+                let location = span.location(source);
+                Error::ShaderSource {
+                    label: label.to_string(),
+                    line: location.line_number,
+                    column: location.line_position,
+                    message,
+                }
+            }
+            None => Error::Shader {
+                label: label.to_string(),
+                message,
+            },
+        });
+    }
+    Ok(())
+}
@@ -0,0 +1,649 @@
+use crate::replay::Replay;
+use crate::view::{Detached, View};
+use crate::{
+    Application, DrawCounts, Error, FixedTimestep, Frame, FrameCapture, FrameStats, FrameTimings,
+    GpuContext, GpuProfiler, InputEvent, InputRecording, InputState, Recorder, Recording,
+    RenderTarget, RendererConfig, Result,
+};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use winit::dpi::PhysicalSize;
+use winit::event::WindowEvent;
+use winit::window::{Window, WindowId};
+
+/// An [`Application`] bound to a GPU context and the targets it renders into.
+///
+/// A `State` starts with one view, the window it was created for or an offscreen texture.
+/// Further windows added with [`add_window`](Self::add_window) share the device and queue, but
+/// each has its own surface and depth buffer. The methods without a window id act on the first
+/// view.
+///
+/// The views are the only surface-level state: [`suspend`](Self::suspend) releases them and
+/// [`resume`](Self::resume) creates them again, while the device, the app and its resources
+/// live on.
+pub struct State<A: Application> {
+    ctx: GpuContext,
+    config: RendererConfig,
+    views: Vec<View>,
+    /// The views released by `suspend`, in order.
+    detached: Vec<Detached>,
+    suspended: bool,
+    app: A,
+    clock: FixedTimestep,
+    alpha: f32,
+    stats: FrameStats,
+    profiler: GpuProfiler,
+    draw_counts: DrawCounts,
+    /// When the previous frame started rendering.
+    last_frame: Option<Instant>,
+    /// Time spent in updates since the previous frame.
+    update_time: Duration,
+    /// The number of updates run so far.
+    frame: u64,
+    input_recording: Option<InputRecording>,
+    /// While set, live input is ignored.
+    replay: Option<Replay>,
+}
+
+impl<A: Application> State<A> {
+    pub async fn new(window: Arc<Window>, config: &RendererConfig) -> Result<Self> {
+        let size = window.inner_size();
+
+        let instance = wgpu::Instance::new(wgpu::InstanceDescriptor {
+            backends: config.backends,
+            ..Default::default()
+        });
+
+        let surface = instance.create_surface(Arc::clone(&window))?;
+
+        let adapter = config
+            .request_adapter(&instance, Some(&surface))
+            .await
+            .ok_or(Error::NoAdapter {
+                backends: config.backends,
+            })?;
+
+        let surface_caps = surface.get_capabilities(&adapter);
+
+        let surface_format = surface_caps
+            .formats
+            .iter()
+            .copied()
+            .find(|f| f.is_srgb())
+            .unwrap_or(surface_caps.formats[0]);
+
+        log::debug!("Present modes: {:?}", surface_caps.present_modes);
+        log::debug!("Surface formats: {:?}", surface_caps.formats);
+        log::debug!("Using surface format {surface_format:?}");
+
+        let ctx = GpuContext::new(instance, adapter, surface_format, size, config).await?;
+        let view = View::window(
+            &ctx,
+            config,
+            window,
+            surface,
+            surface_format,
+            A::depth_format(),
+        )?;
+
+        Self::with_view(ctx, config, view)
+    }
+
+    /// Creates a `State` without a window that renders into an offscreen texture, using the app's
+    /// [`Application::renderer_config`] with every backend allowed.
+    pub async fn new_headless(
+        width: u32,
+        height: u32,
+        format: wgpu::TextureFormat,
+    ) -> Result<Self> {
+        let config = A::renderer_config().backends(wgpu::Backends::all());
+        Self::new_headless_with_config(&config, width, height, format).await
+    }
+
+    pub async fn new_headless_with_config(
+        config: &RendererConfig,
+        width: u32,
+        height: u32,
+        format: wgpu::TextureFormat,
+    ) -> Result<Self> {
+        let ctx = GpuContext::new_headless(config, width, height, format).await?;
+        let view = View::offscreen(&ctx, PhysicalSize::new(width, height), A::depth_format());
+
+        Self::with_view(ctx, config, view)
+    }
+
+    fn with_view(mut ctx: GpuContext, config: &RendererConfig, view: View) -> Result<Self> {
+        ctx.window = view.id();
+        ctx.size = view.size();
+        ctx.input.set_bindings(config.bindings(A::bindings()));
+        let app = A::init(&ctx)?;
+        let profiler = GpuProfiler::new(&ctx.device, &ctx.queue);
+
+        Ok(Self {
+            ctx,
+            config: config.clone(),
+            views: vec![view],
+            detached: Vec::new(),
+            suspended: false,
+            app,
+            clock: FixedTimestep::new(A::timestep()),
+            alpha: 1.0,
+            stats: FrameStats::default(),
+            profiler,
+            draw_counts: DrawCounts::default(),
+            last_frame: None,
+            update_time: Duration::ZERO,
+            frame: 0,
+            input_recording: None,
+            replay: None,
+        })
+    }
+
+    pub fn context(&self) -> &GpuContext {
+        &self.ctx
+    }
+
+    /// The keyboard and mouse the app sees. Changes made here are not recorded, unlike those
+    /// made with [`apply_input`](Self::apply_input).
+    pub fn input_mut(&mut self) -> &mut InputState {
+        &mut self.ctx.input
+    }
+
+    /// The color target of the first view, `None` once every window was closed or while
+    /// suspended.
+    pub fn target(&self) -> Option<&RenderTarget> {
+        self.views.first().map(|view| &view.target)
+    }
+
+    pub fn app(&self) -> &A {
+        &self.app
+    }
+
+    pub fn app_mut(&mut self) -> &mut A {
+        &mut self.app
+    }
+
+    /// CPU timings of the most recent frames.
+    pub fn stats(&self) -> &FrameStats {
+        &self.stats
+    }
+
+    /// What the instrumented render passes of the last frame drew.
+    pub fn draw_counts(&self) -> DrawCounts {
+        self.draw_counts
+    }
+
+    pub fn profiler(&self) -> &GpuProfiler {
+        &self.profiler
+    }
+
+    /// Waits for the GPU so that the profiler has the timings of every frame rendered so far.
+    pub fn flush_profiler(&mut self) {
+        self.profiler.flush(&self.ctx.device);
+    }
+
+    /// The size of the first view.
+    pub fn size(&self) -> PhysicalSize<u32> {
+        self.views.first().map_or(self.ctx.size, View::size)
+    }
+
+    /// Releases the surfaces, or the offscreen texture, and depth buffers of every view, e.g.
+    /// because the platform destroys the native windows while the app is in the background.
+    /// Nothing is rendered until [`resume`](Self::resume).
+    pub fn suspend(&mut self) {
+        let views = std::mem::take(&mut self.views);
+        self.detached.extend(views.into_iter().map(View::detach));
+        self.suspended = true;
+    }
+
+    /// Creates the views released by [`suspend`](Self::suspend) again, with new surfaces for the
+    /// same windows. The app is told about views whose size changed in between.
+    pub fn resume(&mut self) -> Result<()> {
+        self.suspended = false;
+        for detached in std::mem::take(&mut self.detached) {
+            let size = detached.size();
+            let view = View::attach(detached, &self.ctx, &self.config, A::depth_format())?;
+            let new_size = view.size();
+            let resized = new_size != size && new_size.width > 0 && new_size.height > 0;
+            self.views.push(view);
+
+            if resized {
+                self.focus(self.views.len() - 1);
+                self.app.resize(&self.ctx);
+            }
+        }
+        Ok(())
+    }
+
+    pub fn is_suspended(&self) -> bool {
+        self.suspended
+    }
+
+    /// Replaces a lost device: creates a new instance, adapter and device, new surfaces for the
+    /// windows, and creates the [`Restorable`](crate::Restorable) resources of the app again on
+    /// the new device, in the order they were first created. Then [`Application::recreate`] lets
+    /// the app restore what the context does not keep. The windows, the app's other state and
+    /// the memory tracker are kept.
+    pub async fn recover(&mut self) -> Result<()> {
+        let suspended = self.suspended;
+        self.suspend();
+
+        let instance = wgpu::Instance::new(wgpu::InstanceDescriptor {
+            backends: self.config.backends,
+            ..Default::default()
+        });
+        // The new adapter must be able to present to the windows.
+        let surface = match self.windows().next() {
+            Some(window) => Some(instance.create_surface(Arc::clone(window))?),
+            None => None,
+        };
+        let adapter = self
+            .config
+            .request_adapter(&instance, surface.as_ref())
+            .await
+            .ok_or(Error::NoAdapter {
+                backends: self.config.backends,
+            })?;
+        drop(surface);
+
+        let mut ctx = GpuContext::new(
+            instance,
+            adapter,
+            self.ctx.format,
+            self.ctx.size,
+            &self.config,
+        )
+        .await?;
+        ctx.window = self.ctx.window;
+        ctx.memory = self.ctx.memory.clone();
+        ctx.input = std::mem::take(&mut self.ctx.input);
+        // Hot-reloaded shaders keep watching their files across the recovery.
+        ctx.shaders_changed = self.ctx.shader_change_flag();
+        ctx.resources = self.ctx.resources.clone();
+        let lost = std::mem::replace(&mut self.ctx, ctx);
+        self.profiler = GpuProfiler::new(&self.ctx.device, &self.ctx.queue);
+        log::info!(
+            "Recovered from device loss with {:?}",
+            self.ctx.adapter.get_info().name
+        );
+
+        if !suspended {
+            self.resume()?;
+        }
+        if !self.views.is_empty() {
+            self.focus(0);
+        }
+        self.ctx.resources.replay(&self.ctx);
+        self.app.recreate(&self.ctx)?;
+
+        // The app no longer refers to the old device.
+        drop(lost);
+        Ok(())
+    }
+
+    /// The windows rendered to, in the order they were added, including those whose surfaces are
+    /// released while suspended.
+    pub fn windows(&self) -> impl Iterator<Item = &Arc<Window>> {
+        let views = self.views.iter().filter_map(|view| view.window.as_ref());
+        views.chain(self.detached.iter().filter_map(Detached::window))
+    }
+
+    pub fn window(&self, id: WindowId) -> Option<&Arc<Window>> {
+        self.windows().find(|window| window.id() == id)
+    }
+
+    /// Adds a window that renders with the same device. Its surface must support the format of
+    /// the first window, and is only created once resumed when the state is suspended. Calls
+    /// [`Application::window_opened`].
+    pub fn add_window(&mut self, window: Arc<Window>) -> Result<()> {
+        let id = window.id();
+        if self.suspended {
+            // Its surface is created on resume, together with the others.
+            let size = window.inner_size();
+            self.detached.push(Detached::Window { window, size });
+            self.ctx.window = Some(id);
+            self.ctx.size = size;
+        } else {
+            let surface = self.ctx.instance.create_surface(Arc::clone(&window))?;
+            let view = View::window(
+                &self.ctx,
+                &self.config,
+                window,
+                surface,
+                self.ctx.format,
+                A::depth_format(),
+            )?;
+            self.views.push(view);
+            self.focus(self.views.len() - 1);
+        }
+        self.app.window_opened(&self.ctx, id);
+        Ok(())
+    }
+
+    /// Releases the surface and depth buffer of the window and calls
+    /// [`Application::window_closed`]. Returns `false` if the window is unknown.
+    pub fn remove_window(&mut self, id: WindowId) -> bool {
+        if let Some(index) = self.index_of(id) {
+            drop(self.views.remove(index));
+        } else if let Some(index) = self
+            .detached
+            .iter()
+            .position(|detached| detached.window().map(|window| window.id()) == Some(id))
+        {
+            self.detached.remove(index);
+        } else {
+            return false;
+        }
+        self.app.window_closed(&self.ctx, id);
+        true
+    }
+
+    pub fn input(&mut self, event: &WindowEvent) -> bool {
+        if self.views.is_empty() {
+            return false;
+        }
+        self.focus(0);
+        self.handle_input(event) && self.app.input(&self.ctx, event)
+    }
+
+    /// Passes an event of the given window to the app.
+    pub fn window_input(&mut self, id: WindowId, event: &WindowEvent) -> bool {
+        let Some(index) = self.index_of(id) else {
+            return false;
+        };
+        self.focus(index);
+        self.handle_input(event) && self.app.input(&self.ctx, event)
+    }
+
+    /// Applies a window event to `ctx.input`, and returns whether the app gets to handle it.
+    /// During a replay, it doesn't: live input would make the replay diverge.
+    fn handle_input(&mut self, event: &WindowEvent) -> bool {
+        if self.replay.is_some() {
+            return false;
+        }
+        if let Some(event) = InputEvent::from_window_event(event) {
+            self.apply_input(event);
+        }
+        true
+    }
+
+    /// Applies `event` to `ctx.input` as if it came from a window, and records it when input is
+    /// being recorded. Unlike window events, this is not ignored during a replay.
+    pub fn apply_input(&mut self, event: InputEvent) {
+        if let Some(recording) = self.input_recording.as_mut() {
+            recording.push(self.frame, event);
+        }
+        self.ctx.input.apply(&event);
+    }
+
+    /// Starts recording the input, replacing the recording in progress if any. Frames are
+    /// counted from here.
+    pub fn record_input(&mut self) {
+        self.frame = 0;
+        self.input_recording = Some(InputRecording::new(self.clock.step()));
+    }
+
+    /// Stops recording the input and returns what was recorded.
+    pub fn take_input_recording(&mut self) -> Option<InputRecording> {
+        self.input_recording.take()
+    }
+
+    /// Replays `recording` from the next update on. Until its last event was applied, window
+    /// input is ignored: it reaches neither `ctx.input` nor [`Application::input`]. Replaying
+    /// from a fresh state reproduces the recorded session update by update.
+    pub fn replay_input(&mut self, recording: InputRecording) {
+        if recording.timestep != self.clock.step() {
+            log::warn!(
+                "The input was recorded with a timestep of {:?}, the app's is {:?}: the replay \
+                 will diverge",
+                recording.timestep,
+                self.clock.step()
+            );
+        }
+        // Frames count from the start of the replay, which starts with nothing held.
+        self.frame = 0;
+        let bindings = self.ctx.input.bindings().clone();
+        self.ctx.input = InputState::new();
+        self.ctx.input.set_bindings(bindings);
+        self.replay = Some(Replay::new(recording));
+    }
+
+    pub fn is_replaying(&self) -> bool {
+        self.replay.is_some()
+    }
+
+    /// The number of updates run since the state was created, or since the input recording or
+    /// replay started.
+    pub fn frame_index(&self) -> u64 {
+        self.frame
+    }
+
+    pub fn resize(&mut self, new_size: PhysicalSize<u32>) {
+        if !self.views.is_empty() {
+            self.resize_view(0, new_size);
+        }
+    }
+
+    pub fn resize_window(&mut self, id: WindowId, new_size: PhysicalSize<u32>) {
+        if let Some(index) = self.index_of(id) {
+            self.resize_view(index, new_size);
+        }
+    }
+
+    fn resize_view(&mut self, index: usize, new_size: PhysicalSize<u32>) {
+        // A lost device can't configure surfaces; `recover` creates them at the right size.
+        if new_size.width > 0 && new_size.height > 0 && !self.ctx.is_device_lost() {
+            self.views[index].resize(&self.ctx, new_size);
+            self.focus(index);
+            self.app.resize(&self.ctx);
+        }
+    }
+
+    fn index_of(&self, id: WindowId) -> Option<usize> {
+        self.views.iter().position(|view| view.id() == Some(id))
+    }
+
+    /// Points `ctx.window` and `ctx.size` at the view the next hook is called for.
+    fn focus(&mut self, index: usize) {
+        let view = &self.views[index];
+        self.ctx.window = view.id();
+        self.ctx.size = view.size();
+    }
+
+    /// Lets `elapsed` of real time pass: runs as many fixed-rate updates as are due and sets up
+    /// the next frame to interpolate between the last two.
+    pub fn advance(&mut self, elapsed: Duration) {
+        let start = Instant::now();
+        let step = self.clock.step();
+        for _ in 0..self.clock.advance(elapsed) {
+            self.run_update(step);
+        }
+        self.alpha = self.clock.alpha();
+        self.update_time += start.elapsed();
+    }
+
+    /// Lets `duration` of simulated time pass like `advance`, without dropping any of it when it
+    /// is more than the clock catches up with at once.
+    pub fn simulate(&mut self, mut duration: Duration) {
+        let step = self.clock.step();
+        while duration > step {
+            self.advance(step);
+            duration -= step;
+        }
+        self.advance(duration);
+    }
+
+    /// Renders the frames of `recording` into the first view and writes them, advancing the
+    /// simulation by exactly one frame time between frames. Returns where the recording is.
+    pub fn record(&mut self, recording: &Recording) -> Result<PathBuf> {
+        let mut recorder = Recorder::new(recording.clone())?;
+        self.simulate(recording.start_time());
+        while !recorder.is_done() {
+            if recorder.recorded() > 0 {
+                self.simulate(recording.frame_time());
+            }
+            recorder.push(&self.render_to_image()?)?;
+        }
+        recorder.finish()
+    }
+
+    /// Calls [`Application::reload_shaders`] if a watched shader file changed since the last
+    /// call. The runner does so between frames.
+    pub fn reload_shaders(&mut self) {
+        if self.ctx.take_shader_change() {
+            self.app.reload_shaders(&self.ctx);
+        }
+    }
+
+    /// Runs a single update of `dt`, outside of the fixed-rate clock. The next frame shows its
+    /// result without interpolation.
+    pub fn update(&mut self, dt: Duration) {
+        let start = Instant::now();
+        self.run_update(dt);
+        self.alpha = 1.0;
+        self.update_time += start.elapsed();
+    }
+
+    /// Applies the replayed input due, updates the app and starts the next input frame.
+    fn run_update(&mut self, dt: Duration) {
+        if let Some(replay) = self.replay.as_mut() {
+            for input in replay.due(self.frame) {
+                self.ctx.input.apply(&input.event);
+            }
+            if replay.is_done() {
+                self.replay = None;
+            }
+        }
+        self.app.update(&self.ctx, dt);
+        self.ctx.input.end_frame();
+        self.frame += 1;
+    }
+
+    /// Renders a frame into the first view.
+    pub fn render(&mut self) -> Result<()> {
+        if self.views.is_empty() {
+            return Ok(());
+        }
+        self.render_view(0, |_, _, _| ())
+    }
+
+    /// Renders a frame into the given window. Unknown windows are skipped.
+    pub fn render_window(&mut self, id: WindowId) -> Result<()> {
+        match self.index_of(id) {
+            Some(index) => self.render_view(index, |_, _, _| ()),
+            None => Ok(()),
+        }
+    }
+
+    /// Renders a frame like `render` and returns its pixels.
+    ///
+    /// The color target is copied before it is presented. Window surfaces that do not support
+    /// `TextureUsages::COPY_SRC` get the frame drawn a second time, into a copyable texture.
+    pub fn render_to_image(&mut self) -> Result<image::RgbaImage> {
+        if self.views.is_empty() {
+            return Err(Error::Capture {
+                message: "there is no view to render".to_string(),
+            });
+        }
+        self.capture_view(0)
+    }
+
+    /// Renders a frame into the given window like `render_window`, and returns its pixels.
+    pub fn render_window_to_image(&mut self, id: WindowId) -> Result<image::RgbaImage> {
+        let index = self.index_of(id).ok_or_else(|| Error::Capture {
+            message: format!("there is no window {id:?} to render"),
+        })?;
+        self.capture_view(index)
+    }
+
+    fn capture_view(&mut self, index: usize) -> Result<image::RgbaImage> {
+        if self.views[index].target.is_copyable() {
+            let capture = self.render_view(index, FrameCapture::copy_from)??;
+            return capture.read(&self.ctx.device);
+        }
+
+        // The frame is drawn into an offscreen texture of the same size and format standing in
+        // for the surface, then again into the surface so that the window still shows it.
+        let target = &self.views[index].target;
+        let offscreen = RenderTarget::offscreen(
+            &self.ctx.device,
+            target.width(),
+            target.height(),
+            target.format(),
+        );
+        let surface = std::mem::replace(&mut self.views[index].target, offscreen);
+        let captured = self.render_view(index, FrameCapture::copy_from);
+        self.views[index].target = surface;
+        let image = captured??.read(&self.ctx.device)?;
+        self.render_view(index, |_, _, _| ())?;
+        Ok(image)
+    }
+
+    /// Renders a frame into `self.views[index]`. `record` can add commands after the app's, it
+    /// is given the texture that is about to be presented.
+    ///
+    /// Fails with `SurfaceError::Lost` when the device is lost, as nothing can be submitted, and
+    /// with `Error::Gpu` when wgpu reported an error that nothing captured since the last frame.
+    fn render_view<T>(
+        &mut self,
+        index: usize,
+        record: impl FnOnce(&wgpu::Device, &mut wgpu::CommandEncoder, &wgpu::Texture) -> T,
+    ) -> Result<T> {
+        self.focus(index);
+        let view = &self.views[index];
+        if self.ctx.is_device_lost() {
+            return Err(wgpu::SurfaceError::Lost.into());
+        }
+
+        let start = Instant::now();
+        let output = view.target.acquire()?;
+        let acquired = Instant::now();
+
+        // Polling the device is what reports a loss.
+        self.profiler.begin_frame(&self.ctx.device);
+        if self.ctx.is_device_lost() {
+            return Err(wgpu::SurfaceError::Lost.into());
+        }
+        let mut frame = Frame::new(
+            &self.ctx,
+            &output.view,
+            view.depth.as_ref().map(|depth| &depth.view),
+            self.alpha,
+            &self.stats,
+            &mut self.profiler,
+        );
+        self.app.render(&mut frame);
+        let recorded = record(&self.ctx.device, &mut frame.encoder, output.texture());
+        frame.profiler.end_frame(&mut frame.encoder);
+        self.draw_counts = frame.draw_counts;
+        let command_buffer = frame.encoder.finish();
+        let encoded = Instant::now();
+
+        self.ctx.queue.submit(std::iter::once(command_buffer));
+        self.profiler.frame_submitted();
+        output.present();
+
+        self.record_frame(start, acquired, encoded);
+        match self.ctx.take_uncaptured_error() {
+            Some(message) => Err(Error::Gpu { message }),
+            None => Ok(recorded),
+        }
+    }
+
+    /// Records the timings of a frame that started at `start`, got its color target at
+    /// `acquired` and finished encoding at `encoded`. It was presented just now.
+    fn record_frame(&mut self, start: Instant, acquired: Instant, encoded: Instant) {
+        let timings = FrameTimings {
+            // The first frame has no predecessor; count its own duration instead.
+            frame: self
+                .last_frame
+                .map_or_else(|| start.elapsed(), |last| start - last),
+            update: std::mem::take(&mut self.update_time),
+            encode: encoded - acquired,
+            present: (acquired - start) + encoded.elapsed(),
+        };
+        self.stats.record(timings);
+        self.last_frame = Some(start);
+    }
+}
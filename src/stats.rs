@@ -0,0 +1,180 @@
+use std::collections::VecDeque;
+use std::fmt;
+use std::time::Duration;
+
+/// CPU time spent on one frame.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct FrameTimings {
+    /// Time since the previous frame started.
+    pub frame: Duration,
+    /// Time spent in `Application::update`, over all the updates run for this frame.
+    pub update: Duration,
+    /// Time spent recording the frame in `Application::render`.
+    pub encode: Duration,
+    /// Time spent acquiring the surface texture, submitting and presenting.
+    pub present: Duration,
+}
+
+/// Statistics of one kind of timing over the recorded frames.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct TimingSummary {
+    pub average: Duration,
+    pub min: Duration,
+    pub max: Duration,
+    pub p50: Duration,
+    pub p95: Duration,
+    pub p99: Duration,
+}
+
+impl TimingSummary {
+    fn new(mut samples: Vec<Duration>) -> Self {
+        if samples.is_empty() {
+            return Self::default();
+        }
+        samples.sort_unstable();
+
+        let percentile = |p: f64| samples[((samples.len() - 1) as f64 * p).round() as usize];
+        Self {
+            average: samples.iter().sum::<Duration>() / samples.len() as u32,
+            min: samples[0],
+            max: samples[samples.len() - 1],
+            p50: percentile(0.50),
+            p95: percentile(0.95),
+            p99: percentile(0.99),
+        }
+    }
+}
+
+impl fmt::Display for TimingSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "avg {:.2} ms (min {:.2}, p95 {:.2}, max {:.2})",
+            ms(self.average),
+            ms(self.min),
+            ms(self.p95),
+            ms(self.max)
+        )
+    }
+}
+
+fn ms(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
+
+/// Timings of the most recent frames, kept in a fixed-size rolling window.
+#[derive(Clone, Debug)]
+pub struct FrameStats {
+    samples: VecDeque<FrameTimings>,
+    capacity: usize,
+    frame_count: u64,
+}
+
+impl Default for FrameStats {
+    fn default() -> Self {
+        Self::new(120)
+    }
+}
+
+impl FrameStats {
+    /// Keeps the timings of the last `capacity` frames.
+    pub fn new(capacity: usize) -> Self {
+        let capacity = capacity.max(1);
+        Self {
+            samples: VecDeque::with_capacity(capacity),
+            capacity,
+            frame_count: 0,
+        }
+    }
+
+    pub fn record(&mut self, timings: FrameTimings) {
+        if self.samples.len() == self.capacity {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(timings);
+        self.frame_count += 1;
+    }
+
+    /// The number of frames recorded since the start, including those that left the window.
+    pub fn frame_count(&self) -> u64 {
+        self.frame_count
+    }
+
+    pub fn latest(&self) -> Option<&FrameTimings> {
+        self.samples.back()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &FrameTimings> {
+        self.samples.iter()
+    }
+
+    fn summary(&self, timing: impl Fn(&FrameTimings) -> Duration) -> TimingSummary {
+        TimingSummary::new(self.samples.iter().map(timing).collect())
+    }
+
+    pub fn frame(&self) -> TimingSummary {
+        self.summary(|t| t.frame)
+    }
+
+    pub fn update(&self) -> TimingSummary {
+        self.summary(|t| t.update)
+    }
+
+    pub fn encode(&self) -> TimingSummary {
+        self.summary(|t| t.encode)
+    }
+
+    pub fn present(&self) -> TimingSummary {
+        self.summary(|t| t.present)
+    }
+
+    /// Frames per second, from the average frame time.
+    pub fn fps(&self) -> f64 {
+        let average = self.frame().average;
+        if average.is_zero() {
+            0.0
+        } else {
+            1.0 / average.as_secs_f64()
+        }
+    }
+}
+
+impl fmt::Display for FrameStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:.1} fps | frame {} | update {} | encode {} | present {}",
+            self.fps(),
+            self.frame(),
+            self.update(),
+            self.encode(),
+            self.present()
+        )
+    }
+}
+
+/// Where the runner reports [`FrameStats`], and how often.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct StatsReport {
+    /// Log the full statistics at `info` level.
+    pub log: bool,
+    /// Show the frame rate and average frame time in the window title.
+    pub title: bool,
+    pub interval: Duration,
+}
+
+impl Default for StatsReport {
+    fn default() -> Self {
+        Self {
+            log: false,
+            title: false,
+            interval: Duration::from_secs(1),
+        }
+    }
+}
+
+impl StatsReport {
+    pub fn is_enabled(&self) -> bool {
+        self.log || self.title
+    }
+}
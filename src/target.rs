@@ -0,0 +1,148 @@
+/// Where a `State` draws its frames: either a window surface or an owned offscreen texture.
+pub enum RenderTarget {
+    Surface {
+        surface: wgpu::Surface<'static>,
+        config: wgpu::SurfaceConfiguration,
+    },
+    Offscreen {
+        texture: wgpu::Texture,
+    },
+}
+
+/// A color target acquired for a single frame.
+pub struct TargetFrame<'a> {
+    output: FrameOutput<'a>,
+    pub view: wgpu::TextureView,
+}
+
+enum FrameOutput<'a> {
+    Surface(wgpu::SurfaceTexture),
+    Offscreen(&'a wgpu::Texture),
+}
+
+impl TargetFrame<'_> {
+    pub fn texture(&self) -> &wgpu::Texture {
+        match &self.output {
+            FrameOutput::Surface(surface_texture) => &surface_texture.texture,
+            FrameOutput::Offscreen(texture) => texture,
+        }
+    }
+
+    /// Schedules the frame for presentation. Offscreen frames have nothing to present.
+    pub fn present(self) {
+        if let FrameOutput::Surface(surface_texture) = self.output {
+            surface_texture.present();
+        }
+    }
+}
+
+impl RenderTarget {
+    pub fn offscreen(
+        device: &wgpu::Device,
+        width: u32,
+        height: u32,
+        format: wgpu::TextureFormat,
+    ) -> Self {
+        Self::Offscreen {
+            texture: Self::create_offscreen_texture(device, width, height, format),
+        }
+    }
+
+    fn create_offscreen_texture(
+        device: &wgpu::Device,
+        width: u32,
+        height: u32,
+        format: wgpu::TextureFormat,
+    ) -> wgpu::Texture {
+        device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Offscreen Target"),
+            size: wgpu::Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT
+                | wgpu::TextureUsages::TEXTURE_BINDING
+                | wgpu::TextureUsages::COPY_SRC,
+            view_formats: &[],
+        })
+    }
+
+    pub fn format(&self) -> wgpu::TextureFormat {
+        match self {
+            Self::Surface { config, .. } => config.format,
+            Self::Offscreen { texture } => texture.format(),
+        }
+    }
+
+    pub fn width(&self) -> u32 {
+        match self {
+            Self::Surface { config, .. } => config.width,
+            Self::Offscreen { texture } => texture.width(),
+        }
+    }
+
+    pub fn height(&self) -> u32 {
+        match self {
+            Self::Surface { config, .. } => config.height,
+            Self::Offscreen { texture } => texture.height(),
+        }
+    }
+
+    pub fn is_headless(&self) -> bool {
+        matches!(self, Self::Offscreen { .. })
+    }
+
+    /// Whether frames drawn into this target can be read back. Window surfaces only can when
+    /// they were configured with `TextureUsages::COPY_SRC`.
+    pub fn is_copyable(&self) -> bool {
+        match self {
+            Self::Surface { config, .. } => config.usage.contains(wgpu::TextureUsages::COPY_SRC),
+            Self::Offscreen { .. } => true,
+        }
+    }
+
+    /// The offscreen texture, if this target is headless.
+    pub fn texture(&self) -> Option<&wgpu::Texture> {
+        match self {
+            Self::Surface { .. } => None,
+            Self::Offscreen { texture } => Some(texture),
+        }
+    }
+
+    pub fn resize(&mut self, device: &wgpu::Device, width: u32, height: u32) {
+        match self {
+            Self::Surface { surface, config } => {
+                config.width = width;
+                config.height = height;
+                surface.configure(device, config);
+            }
+            Self::Offscreen { texture } => {
+                *texture = Self::create_offscreen_texture(device, width, height, texture.format());
+            }
+        }
+    }
+
+    pub fn acquire(&self) -> Result<TargetFrame<'_>, wgpu::SurfaceError> {
+        match self {
+            Self::Surface { surface, .. } => {
+                let output = surface.get_current_texture()?;
+                let view = output
+                    .texture
+                    .create_view(&wgpu::TextureViewDescriptor::default());
+                Ok(TargetFrame {
+                    output: FrameOutput::Surface(output),
+                    view,
+                })
+            }
+            Self::Offscreen { texture } => Ok(TargetFrame {
+                output: FrameOutput::Offscreen(texture),
+                view: texture.create_view(&wgpu::TextureViewDescriptor::default()),
+            }),
+        }
+    }
+}
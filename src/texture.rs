@@ -0,0 +1,228 @@
+use crate::{Error, MemoryEntry, MemoryTracker, Result};
+use image::GenericImageView;
+
+/// A texture together with a default view and a sampler, ready to be bound to a shader.
+pub struct Texture {
+    pub texture: wgpu::Texture,
+    pub view: wgpu::TextureView,
+    pub sampler: wgpu::Sampler,
+    allocation: Option<MemoryEntry>,
+}
+
+/// How a `Texture` is created.
+///
+/// The default describes a sampled sRGB color texture, as used for images loaded from disk.
+#[derive(Clone, Debug)]
+pub struct TextureOptions<'a> {
+    pub label: Option<&'a str>,
+    pub format: wgpu::TextureFormat,
+    pub usage: wgpu::TextureUsages,
+    pub mip_level_count: u32,
+    pub sample_count: u32,
+    pub sampler: wgpu::SamplerDescriptor<'a>,
+}
+
+impl Default for TextureOptions<'_> {
+    fn default() -> Self {
+        Self {
+            label: None,
+            format: wgpu::TextureFormat::Rgba8UnormSrgb,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+            mip_level_count: 1,
+            sample_count: 1,
+            sampler: wgpu::SamplerDescriptor {
+                address_mode_u: wgpu::AddressMode::ClampToEdge,
+                address_mode_v: wgpu::AddressMode::ClampToEdge,
+                address_mode_w: wgpu::AddressMode::ClampToEdge,
+                mag_filter: wgpu::FilterMode::Linear,
+                min_filter: wgpu::FilterMode::Nearest,
+                mipmap_filter: wgpu::FilterMode::Nearest,
+                ..Default::default()
+            },
+        }
+    }
+}
+
+impl<'a> TextureOptions<'a> {
+    /// A depth buffer with a comparison sampler, so it can also be sampled for shadows.
+    pub fn depth(label: &'a str) -> Self {
+        Self {
+            label: Some(label),
+            format: Texture::DEPTH_FORMAT,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+            sampler: wgpu::SamplerDescriptor {
+                address_mode_u: wgpu::AddressMode::ClampToEdge,
+                address_mode_v: wgpu::AddressMode::ClampToEdge,
+                address_mode_w: wgpu::AddressMode::ClampToEdge,
+                mag_filter: wgpu::FilterMode::Linear,
+                min_filter: wgpu::FilterMode::Linear,
+                mipmap_filter: wgpu::FilterMode::Nearest,
+                compare: Some(wgpu::CompareFunction::LessEqual),
+                lod_min_clamp: 0.0,
+                lod_max_clamp: 100.0,
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+
+    /// A color attachment that can be sampled by a later pass and read back.
+    pub fn render_target(label: &'a str, format: wgpu::TextureFormat) -> Self {
+        Self {
+            label: Some(label),
+            format,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT
+                | wgpu::TextureUsages::TEXTURE_BINDING
+                | wgpu::TextureUsages::COPY_SRC,
+            sampler: wgpu::SamplerDescriptor {
+                mag_filter: wgpu::FilterMode::Linear,
+                min_filter: wgpu::FilterMode::Linear,
+                ..TextureOptions::default().sampler
+            },
+            ..Default::default()
+        }
+    }
+}
+
+impl Texture {
+    pub const DEPTH_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Depth32Float;
+
+    /// Creates an uninitialized texture of the given size.
+    pub fn new(device: &wgpu::Device, width: u32, height: u32, options: &TextureOptions) -> Self {
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: options.label,
+            size: wgpu::Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: options.mip_level_count,
+            sample_count: options.sample_count,
+            dimension: wgpu::TextureDimension::D2,
+            format: options.format,
+            usage: options.usage,
+            view_formats: &[],
+        });
+
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: options.label,
+            ..options.sampler.clone()
+        });
+
+        Self {
+            texture,
+            view,
+            sampler,
+            allocation: None,
+        }
+    }
+
+    /// Records the texture in `memory` until it is dropped.
+    pub fn track(mut self, memory: &MemoryTracker, label: &str) -> Self {
+        self.allocation = Some(memory.track_texture(&self.texture, label));
+        self
+    }
+
+    /// Decodes an encoded image (PNG, JPEG, ...) into a sampled sRGB texture.
+    pub fn from_bytes(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        bytes: &[u8],
+        label: &str,
+    ) -> Result<Self> {
+        let img = image::load_from_memory(bytes).map_err(|e| Error::asset(label, e))?;
+        Self::from_image(device, queue, &img, Some(label))
+    }
+
+    pub fn from_image(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        img: &image::DynamicImage,
+        label: Option<&str>,
+    ) -> Result<Self> {
+        let options = TextureOptions {
+            label,
+            ..Default::default()
+        };
+        Self::from_image_with(device, queue, img, &options)
+    }
+
+    /// Uploads `img` into a texture described by `options`. The format must be an RGBA8 format;
+    /// `COPY_DST` is added to the usage so the pixels can be written.
+    pub fn from_image_with(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        img: &image::DynamicImage,
+        options: &TextureOptions,
+    ) -> Result<Self> {
+        let options = Self::upload_options(options)?;
+        let rgba = img.to_rgba8();
+        let (width, height) = img.dimensions();
+
+        let texture = Self::new(device, width, height, &options);
+        texture.write_rgba(queue, &rgba);
+        Ok(texture)
+    }
+
+    /// `options` for a texture that RGBA8 pixels are written into, with `COPY_DST` added.
+    pub(crate) fn upload_options<'a>(options: &TextureOptions<'a>) -> Result<TextureOptions<'a>> {
+        if !matches!(
+            options.format,
+            wgpu::TextureFormat::Rgba8Unorm | wgpu::TextureFormat::Rgba8UnormSrgb
+        ) {
+            return Err(Error::asset(
+                options.label.unwrap_or("image"),
+                format!("cannot upload an image into a {:?} texture", options.format),
+            ));
+        }
+        Ok(TextureOptions {
+            usage: options.usage | wgpu::TextureUsages::COPY_DST,
+            ..options.clone()
+        })
+    }
+
+    /// Writes RGBA8 pixels over the whole first mip level.
+    pub(crate) fn write_rgba(&self, queue: &wgpu::Queue, rgba: &[u8]) {
+        let size = self.texture.size();
+        queue.write_texture(
+            wgpu::ImageCopyTexture {
+                texture: &self.texture,
+                mip_level: 0,
+                origin: wgpu::Origin3d::ZERO,
+                aspect: wgpu::TextureAspect::All,
+            },
+            rgba,
+            wgpu::ImageDataLayout {
+                offset: 0,
+                bytes_per_row: Some(4 * size.width),
+                rows_per_image: Some(size.height),
+            },
+            size,
+        );
+    }
+
+    pub fn create_depth_texture(
+        device: &wgpu::Device,
+        width: u32,
+        height: u32,
+        label: &str,
+    ) -> Self {
+        Self::new(device, width, height, &TextureOptions::depth(label))
+    }
+
+    pub fn create_render_target(
+        device: &wgpu::Device,
+        width: u32,
+        height: u32,
+        format: wgpu::TextureFormat,
+        label: &str,
+    ) -> Self {
+        Self::new(
+            device,
+            width,
+            height,
+            &TextureOptions::render_target(label, format),
+        )
+    }
+}
@@ -0,0 +1,56 @@
+use std::time::Duration;
+
+/// Turns variable frame times into a whole number of fixed-size update steps.
+///
+/// Time that does not add up to a full step is carried over to the next frame, and
+/// [`alpha`](FixedTimestep::alpha) tells how far the next frame lies between the last two
+/// updates, so rendering can interpolate between them.
+#[derive(Clone, Debug)]
+pub struct FixedTimestep {
+    step: Duration,
+    accumulator: Duration,
+    max_steps: u32,
+}
+
+impl FixedTimestep {
+    pub fn new(step: Duration) -> Self {
+        assert!(!step.is_zero(), "the fixed timestep must not be zero");
+        Self {
+            step,
+            accumulator: Duration::ZERO,
+            max_steps: 8,
+        }
+    }
+
+    /// The largest number of updates run for a single frame. After a long stall (a breakpoint,
+    /// a window drag), the remaining time is dropped instead of trying to catch up with it.
+    pub fn with_max_steps(mut self, max_steps: u32) -> Self {
+        self.max_steps = max_steps.max(1);
+        self
+    }
+
+    pub fn step(&self) -> Duration {
+        self.step
+    }
+
+    /// Adds `elapsed` to the accumulated time and returns how many updates are due.
+    pub fn advance(&mut self, elapsed: Duration) -> u32 {
+        self.accumulator += elapsed;
+
+        let mut steps = 0;
+        while self.accumulator >= self.step {
+            if steps == self.max_steps {
+                self.accumulator = Duration::ZERO;
+                break;
+            }
+            self.accumulator -= self.step;
+            steps += 1;
+        }
+        steps
+    }
+
+    /// The fraction of a step accumulated since the last update, in `0.0..1.0`.
+    pub fn alpha(&self) -> f32 {
+        self.accumulator.as_secs_f32() / self.step.as_secs_f32()
+    }
+}
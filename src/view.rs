@@ -0,0 +1,153 @@
+use crate::texture::{Texture, TextureOptions};
+use crate::{Error, GpuContext, RenderTarget, RendererConfig, Result};
+use std::sync::Arc;
+use winit::dpi::PhysicalSize;
+use winit::window::{Window, WindowId};
+
+/// One color target a `State` renders into, with its own depth buffer: the surface of a window,
+/// or an offscreen texture when headless.
+pub(crate) struct View {
+    pub(crate) window: Option<Arc<Window>>,
+    pub(crate) target: RenderTarget,
+    pub(crate) depth: Option<Texture>,
+}
+
+impl View {
+    /// A view of `window`, drawing into `surface` with the given color format.
+    pub(crate) fn window(
+        ctx: &GpuContext,
+        config: &RendererConfig,
+        window: Arc<Window>,
+        surface: wgpu::Surface<'static>,
+        format: wgpu::TextureFormat,
+        depth_format: Option<wgpu::TextureFormat>,
+    ) -> Result<Self> {
+        let size = window.inner_size();
+        let surface_caps = surface.get_capabilities(&ctx.adapter);
+        if !surface_caps.formats.contains(&format) {
+            return Err(Error::IncompatibleSurface { format });
+        }
+
+        // COPY_SRC lets frames be read back before they are presented.
+        let usage = wgpu::TextureUsages::RENDER_ATTACHMENT
+            | (surface_caps.usages & wgpu::TextureUsages::COPY_SRC);
+
+        let surface_config = wgpu::SurfaceConfiguration {
+            usage,
+            format,
+            width: size.width,
+            height: size.height,
+            present_mode: config.choose_present_mode(&surface_caps.present_modes),
+            alpha_mode: surface_caps.alpha_modes[0],
+            view_formats: vec![],
+            desired_maximum_frame_latency: config.desired_maximum_frame_latency,
+        };
+
+        if size.width > 0 && size.height > 0 {
+            surface.configure(&ctx.device, &surface_config);
+        }
+
+        Ok(Self {
+            window: Some(window),
+            target: RenderTarget::Surface {
+                surface,
+                config: surface_config,
+            },
+            depth: depth_format.map(|format| Self::create_depth(ctx, size, format)),
+        })
+    }
+
+    pub(crate) fn offscreen(
+        ctx: &GpuContext,
+        size: PhysicalSize<u32>,
+        depth_format: Option<wgpu::TextureFormat>,
+    ) -> Self {
+        Self {
+            window: None,
+            target: RenderTarget::offscreen(&ctx.device, size.width, size.height, ctx.format),
+            depth: depth_format.map(|format| Self::create_depth(ctx, size, format)),
+        }
+    }
+
+    fn create_depth(
+        ctx: &GpuContext,
+        size: PhysicalSize<u32>,
+        format: wgpu::TextureFormat,
+    ) -> Texture {
+        let options = TextureOptions {
+            format,
+            ..TextureOptions::depth("Depth Texture")
+        };
+        // A minimized window still gets a valid, if useless, depth buffer.
+        Texture::new(&ctx.device, size.width.max(1), size.height.max(1), &options)
+            .track(&ctx.memory, "Depth Texture")
+    }
+
+    pub(crate) fn id(&self) -> Option<WindowId> {
+        self.window.as_ref().map(|window| window.id())
+    }
+
+    pub(crate) fn size(&self) -> PhysicalSize<u32> {
+        PhysicalSize::new(self.target.width(), self.target.height())
+    }
+
+    /// Releases the surface or offscreen texture and the depth buffer, keeping what is needed to
+    /// create them again.
+    pub(crate) fn detach(self) -> Detached {
+        let size = self.size();
+        match self.window {
+            Some(window) => Detached::Window { window, size },
+            None => Detached::Offscreen { size },
+        }
+    }
+
+    /// Creates the view again after [`detach`](Self::detach), with a new surface for its window.
+    pub(crate) fn attach(
+        detached: Detached,
+        ctx: &GpuContext,
+        config: &RendererConfig,
+        depth_format: Option<wgpu::TextureFormat>,
+    ) -> Result<Self> {
+        match detached {
+            Detached::Window { window, .. } => {
+                let surface = ctx.instance.create_surface(Arc::clone(&window))?;
+                Self::window(ctx, config, window, surface, ctx.format, depth_format)
+            }
+            Detached::Offscreen { size } => Ok(Self::offscreen(ctx, size, depth_format)),
+        }
+    }
+
+    pub(crate) fn resize(&mut self, ctx: &GpuContext, size: PhysicalSize<u32>) {
+        self.target.resize(&ctx.device, size.width, size.height);
+        if let Some(depth) = self.depth.as_mut() {
+            *depth = Self::create_depth(ctx, size, depth.texture.format());
+        }
+    }
+}
+
+/// A [`View`] while the app is suspended.
+pub(crate) enum Detached {
+    Window {
+        window: Arc<Window>,
+        /// The size of the surface when it was released.
+        size: PhysicalSize<u32>,
+    },
+    Offscreen {
+        size: PhysicalSize<u32>,
+    },
+}
+
+impl Detached {
+    pub(crate) fn window(&self) -> Option<&Arc<Window>> {
+        match self {
+            Self::Window { window, .. } => Some(window),
+            Self::Offscreen { .. } => None,
+        }
+    }
+
+    pub(crate) fn size(&self) -> PhysicalSize<u32> {
+        match self {
+            Self::Window { size, .. } | Self::Offscreen { size } => *size,
+        }
+    }
+}
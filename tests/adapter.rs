@@ -0,0 +1,16 @@
+use wgpu_template::{AdapterSelector, Error, GpuContext, RendererConfig};
+
+#[test]
+fn a_selection_that_matches_no_adapter_fails() {
+    let config = RendererConfig::headless().adapter(AdapterSelector {
+        name: Some("no such adapter".to_string()),
+        ..Default::default()
+    });
+    let result = pollster::block_on(GpuContext::new_headless(
+        &config,
+        64,
+        64,
+        wgpu::TextureFormat::Rgba8UnormSrgb,
+    ));
+    assert!(matches!(result, Err(Error::NoAdapter { .. })));
+}
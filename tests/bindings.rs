@@ -0,0 +1,125 @@
+use std::path::Path;
+use wgpu_template::{action, Binding, Bindings, InputEvent, InputState};
+use winit::event::MouseButton;
+use winit::keyboard::KeyCode;
+
+mod common;
+
+#[allow(dead_code)]
+#[path = "../examples/triangle/mod.rs"]
+mod triangle;
+
+fn write(name: &str, contents: &str) -> std::path::PathBuf {
+    let path = Path::new(env!("CARGO_TARGET_TMPDIR")).join(name);
+    std::fs::write(&path, contents).unwrap();
+    path
+}
+
+#[test]
+fn bindings_are_written_by_name() {
+    for (text, binding) in [
+        ("KeyW", Binding::Key(KeyCode::KeyW)),
+        ("ArrowUp", Binding::Key(KeyCode::ArrowUp)),
+        ("MouseLeft", Binding::Mouse(MouseButton::Left)),
+        ("Mouse7", Binding::Mouse(MouseButton::Other(7))),
+    ] {
+        assert_eq!(text.parse::<Binding>().unwrap(), binding);
+        assert_eq!(binding.to_string(), text);
+    }
+    assert!("KeyÖ".parse::<Binding>().is_err());
+    assert!("MouseWheel".parse::<Binding>().is_err());
+}
+
+#[test]
+fn files_override_the_actions_they_list() {
+    // An AZERTY layout: Z and Q are where W and A are on QWERTY.
+    let toml = write(
+        "azerty.toml",
+        "move_forward = [\"KeyZ\", \"ArrowUp\"]\nmove_left = [\"KeyQ\"]\nfire = [\"MouseLeft\"]\n",
+    );
+    let ron = write(
+        "azerty.ron",
+        "{\"move_forward\": [\"KeyZ\", \"ArrowUp\"], \"move_left\": [\"KeyQ\"], \"fire\": [\"MouseLeft\"]}",
+    );
+
+    for path in [toml, ron] {
+        let mut bindings = Bindings::default();
+        bindings.merge(Bindings::load(&path).unwrap());
+        assert_eq!(
+            bindings.get(action::MOVE_FORWARD),
+            [Binding::Key(KeyCode::KeyZ), Binding::Key(KeyCode::ArrowUp)]
+        );
+        assert_eq!(
+            bindings.get(action::MOVE_LEFT),
+            [Binding::Key(KeyCode::KeyQ)]
+        );
+        assert_eq!(bindings.get("fire"), [Binding::Mouse(MouseButton::Left)]);
+        // The others keep their defaults.
+        assert_eq!(bindings.get(action::QUIT), [Binding::Key(KeyCode::Escape)]);
+        assert_eq!(
+            bindings.get(action::SCREENSHOT),
+            [Binding::Key(KeyCode::F12)]
+        );
+    }
+
+    let broken = write("broken.toml", "move_forward = [\"KeyNope\"]\n");
+    let error = format!("{:#}", Bindings::load(&broken).unwrap_err());
+    assert!(error.contains("KeyNope"), "{error}");
+}
+
+#[test]
+fn actions_follow_any_of_their_bindings() {
+    let mut input = InputState::new();
+    input.set_bindings(Bindings::default().bind(
+        "fire",
+        [
+            Binding::Key(KeyCode::Space),
+            Binding::Mouse(MouseButton::Left),
+        ],
+    ));
+
+    input.apply(&InputEvent::MouseButton {
+        button: MouseButton::Left,
+        pressed: true,
+    });
+    assert!(input.is_action_pressed("fire"));
+    assert!(input.is_action_just_pressed("fire"));
+    assert!(!input.is_action_pressed(action::MOVE_FORWARD));
+
+    input.end_frame();
+    input.apply(&InputEvent::Key {
+        key: KeyCode::ArrowUp,
+        pressed: true,
+    });
+    input.apply(&InputEvent::MouseButton {
+        button: MouseButton::Left,
+        pressed: false,
+    });
+    assert!(!input.is_action_pressed("fire"));
+    assert!(input.is_action_just_released("fire"));
+    assert!(input.is_action_pressed(action::MOVE_FORWARD));
+    assert!(!input.is_action_pressed("unknown"));
+}
+
+#[test]
+fn the_state_applies_the_bindings_file_to_the_apps_bindings() {
+    let path = write("triangle.toml", "toggle_shader = [\"KeyT\"]\n");
+    let config = common::config::<triangle::Example>().bindings_file(path);
+    let Some(state) = common::state_with::<triangle::Example>(&config, 64, 64) else {
+        return;
+    };
+
+    let bindings = state.context().input.bindings();
+    assert_eq!(bindings.get("toggle_shader"), [Binding::Key(KeyCode::KeyT)]);
+    assert_eq!(
+        bindings.get("toggle_wireframe"),
+        [Binding::Key(KeyCode::KeyL)]
+    );
+
+    let listing = bindings.to_string();
+    assert!(listing.contains("toggle_shader     KeyT\n"), "{listing}");
+    assert!(
+        listing.contains("move_forward      KeyW, ArrowUp\n"),
+        "{listing}"
+    );
+}
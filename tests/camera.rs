@@ -0,0 +1,70 @@
+use cgmath::{InnerSpace, Point3, Vector3};
+use std::time::Duration;
+use wgpu_template::camera::{Camera, CameraController, FlyController, OrbitController, Projection};
+use wgpu_template::{action, Bindings, InputEvent, InputState};
+use winit::keyboard::KeyCode;
+
+fn camera() -> Camera {
+    Camera {
+        eye: Point3::new(0.0, 0.0, 10.0),
+        target: Point3::new(0.0, 0.0, 0.0),
+        up: Vector3::unit_y(),
+        projection: Projection::Perspective {
+            fovy: 45.0,
+            aspect: 1.0,
+            znear: 0.1,
+            zfar: 100.0,
+        },
+    }
+}
+
+fn holding(key: KeyCode) -> InputState {
+    let mut input = InputState::new();
+    input.set_bindings(Bindings::default());
+    input.apply(&InputEvent::Key { key, pressed: true });
+    input
+}
+
+/// Runs `controller` for a second of updates at `rate` per second.
+fn one_second(controller: &mut dyn CameraController, input: &InputState, rate: u32) -> Camera {
+    let mut camera = camera();
+    for _ in 0..rate {
+        controller.update_camera(&mut camera, input, Duration::from_secs(1) / rate);
+    }
+    camera
+}
+
+#[test]
+fn controllers_move_as_far_per_second_whatever_the_update_rate() {
+    let forward = holding(KeyCode::KeyW);
+    assert!(forward.is_action_pressed(action::MOVE_FORWARD));
+
+    let mut fly = FlyController::new(3.0, 1.8);
+    for rate in [30, 60, 144] {
+        let moved = one_second(&mut fly, &forward, rate);
+        assert!(
+            (moved.eye.z - 7.0).abs() < 1e-3,
+            "{:?} at {rate} Hz",
+            moved.eye
+        );
+    }
+
+    let mut orbit = OrbitController::new(2.0);
+    for rate in [30, 60, 144] {
+        let moved = one_second(&mut orbit, &forward, rate);
+        assert!(
+            (moved.eye.z - 8.0).abs() < 1e-3,
+            "{:?} at {rate} Hz",
+            moved.eye
+        );
+    }
+
+    // Turning covers `turn_speed` radians per second.
+    let left = holding(KeyCode::ArrowLeft);
+    for rate in [30, 60, 144] {
+        let turned = one_second(&mut fly, &left, rate);
+        let view = (turned.target - turned.eye).normalize();
+        let angle = view.angle(-Vector3::unit_z()).0;
+        assert!((angle - 1.8).abs() < 1e-3, "{angle} at {rate} Hz");
+    }
+}
@@ -0,0 +1,59 @@
+//! Fixtures shared by the integration tests.
+//!
+//! Everything renders on the software adapter, so that results don't depend on the host's GPU.
+//! Where there is none, the fixtures return `None` and the tests skip themselves, unless the `CI`
+//! environment variable is set: there, a missing adapter fails the test instead of hiding it.
+#![allow(dead_code)]
+
+use wgpu_template::{Application, Error, GpuContext, RendererConfig, State};
+
+pub const FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba8UnormSrgb;
+
+/// `config` restricted to the software adapter, of any backend.
+pub fn software(config: RendererConfig) -> RendererConfig {
+    config
+        .backends(wgpu::Backends::all())
+        .force_fallback_adapter(true)
+}
+
+/// `A`'s own configuration, on the software adapter.
+pub fn config<A: Application>() -> RendererConfig {
+    software(A::renderer_config())
+}
+
+/// A headless 64×64 `State` for `A`.
+pub fn state<A: Application>() -> Option<State<A>> {
+    state_with(&config::<A>(), 64, 64)
+}
+
+/// A headless `State` for `A`, with `config` and the given size.
+pub fn state_with<A: Application>(
+    config: &RendererConfig,
+    width: u32,
+    height: u32,
+) -> Option<State<A>> {
+    skip_without_adapter(pollster::block_on(State::new_headless_with_config(
+        config, width, height, FORMAT,
+    )))
+}
+
+/// A headless 64×64 `GpuContext`, with `config` on the software adapter.
+pub fn context(config: RendererConfig) -> Option<GpuContext> {
+    skip_without_adapter(pollster::block_on(GpuContext::new_headless(
+        &software(config),
+        64,
+        64,
+        FORMAT,
+    )))
+}
+
+fn skip_without_adapter<T>(result: wgpu_template::Result<T>) -> Option<T> {
+    match result {
+        Ok(value) => Some(value),
+        Err(Error::NoAdapter { backends }) if std::env::var_os("CI").is_none() => {
+            eprintln!("Skipping: no software adapter for {backends:?}");
+            None
+        }
+        Err(e) => panic!("{e}"),
+    }
+}
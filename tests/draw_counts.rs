@@ -0,0 +1,32 @@
+use std::time::Duration;
+
+mod common;
+
+#[allow(dead_code, clippy::module_inception)]
+#[path = "../examples/model/mod.rs"]
+mod model;
+
+#[test]
+fn model_draws_are_counted() {
+    let Some(mut state) = common::state::<model::Example>() else {
+        return;
+    };
+    state.update(Duration::ZERO);
+    state.render().unwrap();
+
+    // One instanced draw per mesh, each drawing the 10x10 grid of instances.
+    let counts = state.draw_counts();
+    assert!(counts.draw_calls > 0);
+    assert_eq!(counts.instances, 100 * counts.draw_calls as u64);
+    assert_eq!(counts.indices, 3 * counts.triangles);
+    assert_eq!(counts.vertices, 0);
+    assert_eq!(counts.pipeline_switches, 1);
+    // The instance buffer, then a vertex and an index buffer per mesh.
+    assert_eq!(counts.buffer_bindings, 1 + 2 * counts.draw_calls);
+    // Material and camera bind groups are only counted when they change.
+    assert!((2..=2 * counts.draw_calls).contains(&counts.bind_group_switches));
+
+    // Counts start over every frame.
+    state.render().unwrap();
+    assert_eq!(state.draw_counts(), counts);
+}
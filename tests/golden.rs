@@ -0,0 +1,240 @@
+//! Golden-image regression tests.
+//!
+//! Every example is rendered headlessly at a fixed size and compared with the reference PNG in
+//! `tests/golden/`. When a frame does not match, the rendered frame and a diff image are written
+//! to `target/tmp/golden/`.
+//!
+//! Frames are rendered on the software adapter, so that they don't depend on the host's GPU; the
+//! tests are skipped where there is none, except on CI. Run with `UPDATE_GOLDEN=1` to (re)generate the reference
+//! images.
+
+use image::{Rgba, RgbaImage};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use wgpu_template::{Application, InputRecording, State};
+
+mod common;
+
+#[allow(dead_code)]
+#[path = "../examples/triangle/mod.rs"]
+mod triangle;
+
+#[allow(dead_code)]
+#[path = "../examples/buffer/mod.rs"]
+mod buffer;
+
+#[allow(dead_code)]
+#[path = "../examples/texture/mod.rs"]
+mod texture;
+
+#[allow(dead_code)]
+#[path = "../examples/uniform/mod.rs"]
+mod uniform;
+
+#[allow(dead_code)]
+#[path = "../examples/instance/mod.rs"]
+mod instance;
+
+#[allow(dead_code, non_snake_case)]
+#[path = "../examples/depthBuffer/mod.rs"]
+mod depthBuffer;
+
+#[allow(dead_code, clippy::module_inception)]
+#[path = "../examples/model/mod.rs"]
+mod model;
+
+#[allow(dead_code)]
+#[path = "../examples/windows/mod.rs"]
+mod windows;
+
+const WIDTH: u32 = 256;
+const HEIGHT: u32 = 256;
+
+/// How far a rendered frame may drift from its reference.
+struct Tolerance {
+    /// Largest difference allowed in any channel of a pixel.
+    per_channel: u8,
+    /// Fraction of pixels allowed to exceed `per_channel`, to absorb rasterization differences
+    /// along triangle edges between adapters.
+    max_failing_ratio: f32,
+}
+
+const TOLERANCE: Tolerance = Tolerance {
+    per_channel: 2,
+    max_failing_ratio: 0.005,
+};
+
+fn reference_path(name: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/golden")
+        .join(format!("{name}.png"))
+}
+
+fn output_dir() -> PathBuf {
+    Path::new(env!("CARGO_TARGET_TMPDIR")).join("golden")
+}
+
+fn new_state<A: Application>() -> Option<State<A>> {
+    common::state_with(&common::config::<A>(), WIDTH, HEIGHT)
+}
+
+fn render<A: Application>() -> Option<RgbaImage> {
+    let mut state = new_state::<A>()?;
+    state.update(Duration::ZERO);
+    Some(state.render_to_image().unwrap())
+}
+
+/// Renders `A` after replaying the input recorded in `tests/input/{input}.ron`, one update per
+/// recorded frame.
+fn render_replay<A: Application>(input: &str) -> Option<RgbaImage> {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/input")
+        .join(format!("{input}.ron"));
+    let recording = InputRecording::load(&path).unwrap();
+    let frames = recording.frame_count();
+
+    let mut state = new_state::<A>()?;
+    state.replay_input(recording);
+    for _ in 0..frames {
+        state.update(A::timestep());
+    }
+    assert!(!state.is_replaying());
+    Some(state.render_to_image().unwrap())
+}
+
+/// Compares `actual` with the reference image `name`. `None` means the frame could not be
+/// rendered for lack of a software adapter, and skips the check.
+fn check_golden(name: &str, actual: Option<RgbaImage>) {
+    let Some(actual) = actual else {
+        return;
+    };
+    let reference_path = reference_path(name);
+
+    if std::env::var_os("UPDATE_GOLDEN").is_some() {
+        actual.save(&reference_path).unwrap();
+        println!("Wrote reference image {reference_path:?}");
+        return;
+    }
+    assert!(
+        reference_path.exists(),
+        "{name}: no reference image at {reference_path:?}, run with UPDATE_GOLDEN=1 to create it"
+    );
+
+    let expected = image::open(&reference_path).unwrap().to_rgba8();
+    assert_eq!(
+        expected.dimensions(),
+        actual.dimensions(),
+        "{name}: size differs from {reference_path:?}"
+    );
+
+    let mut diff = RgbaImage::new(actual.width(), actual.height());
+    let mut failing = 0;
+    for (x, y, pixel) in actual.enumerate_pixels() {
+        let distance = pixel
+            .0
+            .iter()
+            .zip(expected.get_pixel(x, y).0)
+            .map(|(a, e)| a.abs_diff(e))
+            .max()
+            .unwrap();
+
+        if distance > TOLERANCE.per_channel {
+            failing += 1;
+            diff.put_pixel(x, y, Rgba([255, 0, 0, 255]));
+        } else {
+            // Keep a dimmed copy of the frame so the failing pixels can be located.
+            let [r, g, b, _] = pixel.0;
+            diff.put_pixel(x, y, Rgba([r / 4, g / 4, b / 4, 255]));
+        }
+    }
+
+    let failing_ratio = failing as f32 / (actual.width() * actual.height()) as f32;
+    if failing_ratio > TOLERANCE.max_failing_ratio {
+        let output_dir = output_dir();
+        std::fs::create_dir_all(&output_dir).unwrap();
+        let actual_path = output_dir.join(format!("{name}-actual.png"));
+        let diff_path = output_dir.join(format!("{name}-diff.png"));
+        actual.save(&actual_path).unwrap();
+        diff.save(&diff_path).unwrap();
+
+        panic!(
+            "{name}: {failing} pixels ({:.2}%) differ from {reference_path:?}, see {actual_path:?} and {diff_path:?}",
+            failing_ratio * 100.0
+        );
+    }
+}
+
+#[test]
+fn golden_triangle() {
+    check_golden("triangle", render::<triangle::Example>());
+}
+
+#[test]
+fn golden_buffer() {
+    check_golden("buffer", render::<buffer::Example>());
+}
+
+#[test]
+fn golden_texture() {
+    check_golden("texture", render::<texture::Example>());
+}
+
+#[test]
+fn golden_uniform() {
+    check_golden("uniform", render::<uniform::Example>());
+}
+
+#[test]
+fn golden_uniform_orbit() {
+    check_golden("uniform_orbit", render_replay::<uniform::Example>("orbit"));
+}
+
+#[test]
+fn golden_instance() {
+    check_golden("instance", render::<instance::Example>());
+}
+
+#[test]
+fn golden_depth_buffer() {
+    // Seen from the front, the rows are drawn back to front and the depth buffer changes nothing.
+    check_golden("depthBuffer", render_replay::<depthBuffer::Example>("side"));
+}
+
+#[test]
+fn depth_buffer_hides_what_is_behind() {
+    // `instance` draws the same scene without a depth buffer, so wherever rows are drawn over
+    // nearer ones the two frames differ.
+    let (Some(with_depth), Some(without_depth)) = (
+        render_replay::<depthBuffer::Example>("side"),
+        render_replay::<instance::Example>("side"),
+    ) else {
+        return;
+    };
+    assert!(
+        with_depth != without_depth,
+        "the depth buffer does not change the frame"
+    );
+}
+
+#[test]
+fn golden_depth_buffer_orbit() {
+    check_golden(
+        "depthBuffer_orbit",
+        render_replay::<depthBuffer::Example>("orbit"),
+    );
+}
+
+#[test]
+fn golden_model() {
+    check_golden("model", render::<model::Example>());
+}
+
+#[test]
+fn golden_windows() {
+    // Let the triangles turn apart, so the depth buffer decides which one is in front.
+    let Some(mut state) = new_state::<windows::Example>() else {
+        return;
+    };
+    state.update(Duration::from_millis(500));
+    check_golden("windows", Some(state.render_to_image().unwrap()));
+}
@@ -0,0 +1,135 @@
+use std::path::Path;
+use std::time::{Duration, Instant};
+use wgpu_template::{Error, GpuContext, HotShader, RendererConfig, Restorable, ShaderFile};
+
+mod common;
+
+const SHADER: &str = include_str!("../examples/triangle/shader.wgsl");
+
+fn compile(
+    ctx: &GpuContext,
+    source: &str,
+) -> wgpu_template::Result<Restorable<wgpu::ShaderModule>> {
+    ctx.try_create_shader_module(wgpu::ShaderModuleDescriptor {
+        label: Some("broken.wgsl"),
+        source: wgpu::ShaderSource::Wgsl(source.into()),
+    })
+}
+
+fn location(error: &Error) -> (u32, u32) {
+    match error {
+        Error::ShaderSource { line, column, .. } => (*line, *column),
+        _ => panic!("no location in {error}"),
+    }
+}
+
+/// Saves like editors do, so the watcher never sees a half-written file.
+fn save(path: &Path, contents: &str) {
+    let temporary = path.with_extension("tmp");
+    std::fs::write(&temporary, contents).unwrap();
+    std::fs::rename(&temporary, path).unwrap();
+}
+
+/// Reloads `shader` until the watcher noticed the change.
+fn wait_for_reload(shader: &mut HotShader, ctx: &GpuContext) -> bool {
+    let start = Instant::now();
+    while start.elapsed() < Duration::from_secs(5) {
+        let error_before = shader.error().map(ToString::to_string);
+        if shader.reload(ctx).is_some() {
+            return true;
+        }
+        if shader.error().map(ToString::to_string) != error_before {
+            return false;
+        }
+        std::thread::sleep(Duration::from_millis(20));
+    }
+    panic!("{:?} was not reloaded", shader.path());
+}
+
+#[test]
+fn shader_errors_point_at_their_line_and_column() {
+    let Some(ctx) = common::context(RendererConfig::headless()) else {
+        return;
+    };
+
+    let syntax = SHADER.replace("var out: VertexOutput;", "var out: VertexOutput");
+    let error = compile(&ctx, &syntax).unwrap_err();
+    assert_eq!(location(&error), (10, 5), "{error}");
+    assert!(error
+        .to_string()
+        .starts_with("shader \"broken.wgsl\" is invalid at 10:5: "));
+
+    let types = SHADER.replace("return vec4<f32>(0.3, 0.2, 0.1, 1.0);", "return 1.0;");
+    let error = compile(&ctx, &types).unwrap_err();
+    assert_eq!(location(&error).0, 18, "{error}");
+
+    assert!(compile(&ctx, SHADER).is_ok());
+}
+
+#[test]
+fn a_broken_save_keeps_the_last_good_shader() {
+    let path = concat!(env!("CARGO_TARGET_TMPDIR"), "/hot_reload/shader.wgsl");
+    std::fs::create_dir_all(Path::new(path).parent().unwrap()).unwrap();
+    let green = SHADER.replace("0.3, 0.2, 0.1", "0.0, 1.0, 0.0");
+    std::fs::write(path, &green).unwrap();
+    let file = ShaderFile {
+        path,
+        source: SHADER,
+    };
+
+    // Without hot reloading, the embedded source is used and the file isn't watched.
+    let Some(ctx) = common::context(RendererConfig::headless()) else {
+        return;
+    };
+    let shader = HotShader::new(&ctx, file).unwrap();
+    assert!(!shader.is_watched());
+
+    let ctx = common::context(RendererConfig::headless().hot_reload(true)).unwrap();
+    let mut shader = HotShader::new(&ctx, file).unwrap();
+    assert!(shader.is_watched());
+    assert!(shader.error().is_none());
+
+    save(
+        Path::new(path),
+        &green.replace("fn fs_main(", "fn fs_main(,"),
+    );
+    assert!(!wait_for_reload(&mut shader, &ctx));
+    assert_eq!(location(shader.error().unwrap()), (17, 12));
+
+    save(Path::new(path), &green);
+    assert!(wait_for_reload(&mut shader, &ctx));
+    assert!(shader.error().is_none());
+}
+
+#[test]
+fn pipelines_that_no_longer_match_their_shader_are_reported() {
+    let Some(ctx) = common::context(RendererConfig::headless()) else {
+        return;
+    };
+    let renamed = compile(&ctx, &SHADER.replace("fn fs_main", "fn fragment")).unwrap();
+    let module = renamed.get();
+
+    let error = ctx
+        .try_create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Render Pipeline"),
+            layout: None,
+            vertex: wgpu::VertexState {
+                module: &module,
+                entry_point: "vs_main",
+                buffers: &[],
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &module,
+                entry_point: "fs_main",
+                targets: &[Some(ctx.format.into())],
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+            }),
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+        })
+        .unwrap_err();
+    assert!(matches!(error, Error::Pipeline { .. }), "{error}");
+}
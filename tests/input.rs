@@ -0,0 +1,89 @@
+use cgmath::Vector2;
+use wgpu_template::{InputEvent, InputState};
+use winit::dpi::PhysicalPosition;
+use winit::event::MouseButton;
+use winit::keyboard::{KeyCode, ModifiersState};
+
+fn key(key: KeyCode, pressed: bool) -> InputEvent {
+    InputEvent::Key { key, pressed }
+}
+
+#[test]
+fn edges_last_until_the_end_of_the_frame() {
+    let mut input = InputState::new();
+    input.apply(&key(KeyCode::KeyW, true));
+    // A second press of a held key is no edge.
+    input.apply(&key(KeyCode::KeyW, true));
+    input.apply(&InputEvent::MouseButton {
+        button: MouseButton::Left,
+        pressed: true,
+    });
+    assert!(input.is_key_pressed(KeyCode::KeyW));
+    assert!(input.is_key_just_pressed(KeyCode::KeyW));
+    assert!(input.is_mouse_just_pressed(MouseButton::Left));
+
+    input.end_frame();
+    assert!(input.is_key_pressed(KeyCode::KeyW));
+    assert!(!input.is_key_just_pressed(KeyCode::KeyW));
+    assert!(input.is_mouse_pressed(MouseButton::Left));
+    assert!(!input.is_mouse_just_pressed(MouseButton::Left));
+
+    // Pressed and released within one frame: both edges, nothing held.
+    input.apply(&key(KeyCode::Space, true));
+    input.apply(&key(KeyCode::Space, false));
+    input.apply(&key(KeyCode::KeyW, false));
+    assert!(input.is_key_just_pressed(KeyCode::Space));
+    assert!(input.is_key_just_released(KeyCode::Space));
+    assert!(!input.is_key_pressed(KeyCode::Space));
+    assert!(input.is_key_just_released(KeyCode::KeyW));
+    assert!(!input.is_key_pressed(KeyCode::KeyW));
+}
+
+#[test]
+fn cursor_movement_and_scrolling_add_up_over_the_frame() {
+    let mut input = InputState::new();
+    input.apply(&InputEvent::CursorMoved(PhysicalPosition::new(10.0, 10.0)));
+    assert_eq!(input.cursor_delta(), Vector2::new(0.0, 0.0));
+
+    input.apply(&InputEvent::CursorMoved(PhysicalPosition::new(15.0, 8.0)));
+    input.apply(&InputEvent::CursorMoved(PhysicalPosition::new(20.0, 12.0)));
+    input.apply(&InputEvent::Scroll(Vector2::new(0.0, 1.0)));
+    input.apply(&InputEvent::Scroll(Vector2::new(0.0, 2.0)));
+    assert_eq!(
+        input.cursor_position(),
+        Some(PhysicalPosition::new(20.0, 12.0))
+    );
+    assert_eq!(input.cursor_delta(), Vector2::new(10.0, 2.0));
+    assert_eq!(input.scroll_delta(), Vector2::new(0.0, 3.0));
+
+    input.end_frame();
+    assert_eq!(input.cursor_delta(), Vector2::new(0.0, 0.0));
+    assert_eq!(input.scroll_delta(), Vector2::new(0.0, 0.0));
+
+    // Leaving and entering elsewhere is no movement.
+    input.apply(&InputEvent::CursorLeft);
+    assert_eq!(input.cursor_position(), None);
+    input.apply(&InputEvent::CursorMoved(PhysicalPosition::new(
+        100.0, 100.0,
+    )));
+    assert_eq!(input.cursor_delta(), Vector2::new(0.0, 0.0));
+}
+
+#[test]
+fn losing_the_focus_releases_everything() {
+    let mut input = InputState::new();
+    input.apply(&key(KeyCode::ShiftLeft, true));
+    input.apply(&InputEvent::Modifiers(ModifiersState::SHIFT));
+    input.apply(&InputEvent::MouseButton {
+        button: MouseButton::Right,
+        pressed: true,
+    });
+    assert_eq!(input.modifiers(), ModifiersState::SHIFT);
+    input.end_frame();
+
+    input.apply(&InputEvent::FocusLost);
+    assert!(!input.is_key_pressed(KeyCode::ShiftLeft));
+    assert!(input.is_key_just_released(KeyCode::ShiftLeft));
+    assert!(input.is_mouse_just_released(MouseButton::Right));
+    assert_eq!(input.modifiers(), ModifiersState::empty());
+}
@@ -0,0 +1,99 @@
+use std::sync::Arc;
+use std::time::Duration;
+use wgpu_template::{Error, MemoryCategory};
+
+mod common;
+
+#[allow(dead_code, clippy::module_inception)]
+#[path = "../examples/model/mod.rs"]
+mod model;
+#[allow(dead_code)]
+#[path = "../examples/windows/mod.rs"]
+mod windows;
+
+/// Loses the device of `state`, once the GPU is idle, as a driver reset would.
+fn lose_device<A: wgpu_template::Application>(state: &wgpu_template::State<A>) {
+    state.context().device.destroy();
+    state.context().device.poll(wgpu::Maintain::Wait);
+    assert!(state.context().is_device_lost());
+}
+
+#[test]
+fn suspend_and_resume_keep_the_device_resources() {
+    let Some(mut state) = common::state::<windows::Example>() else {
+        return;
+    };
+    let memory = state.context().memory.clone();
+    state.update(Duration::from_millis(500));
+    let before = state.render_to_image().unwrap();
+    let uniforms = memory.usage(MemoryCategory::Uniform);
+
+    // `suspended`: the target and its depth buffer go, the app's buffers stay.
+    state.suspend();
+    assert!(state.is_suspended());
+    assert!(state.target().is_none());
+    assert_eq!(memory.usage(MemoryCategory::Depth).current, 0);
+    assert_eq!(memory.usage(MemoryCategory::Uniform), uniforms);
+    state.render().unwrap();
+    state.suspend();
+
+    // `resumed`: the same frame as before, drawn with the same resources.
+    state.resume().unwrap();
+    assert!(!state.is_suspended());
+    assert_eq!(state.size(), winit::dpi::PhysicalSize::new(64, 64));
+    assert_eq!(memory.usage(MemoryCategory::Depth).current, 64 * 64 * 4);
+    assert_eq!(memory.usage(MemoryCategory::Uniform), uniforms);
+    assert_eq!(state.render_to_image().unwrap(), before);
+}
+
+#[test]
+fn a_lost_device_is_replaced() {
+    let Some(mut state) = common::state::<windows::Example>() else {
+        return;
+    };
+    let memory = state.context().memory.clone();
+    state.update(Duration::from_millis(500));
+    let before = state.render_to_image().unwrap();
+    let total = memory.total().current;
+
+    lose_device(&state);
+    assert!(matches!(
+        state.render(),
+        Err(Error::Surface(wgpu::SurfaceError::Lost))
+    ));
+
+    pollster::block_on(state.recover()).unwrap();
+    assert!(!state.context().is_device_lost());
+    // The old resources were released, the new ones show the scene as it was.
+    assert_eq!(memory.total().current, total);
+    assert_eq!(state.render_to_image().unwrap(), before);
+}
+
+#[test]
+fn a_lost_device_restores_what_was_created_through_the_context() {
+    let Some(mut state) = common::state::<model::Example>() else {
+        return;
+    };
+    let memory = state.context().memory.clone();
+    let before = state.render_to_image().unwrap();
+    let total = memory.total().current;
+    let vertices = state
+        .context()
+        .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("vertices"),
+            contents: &[0; 64],
+            usage: wgpu::BufferUsages::VERTEX,
+        });
+    let lost = vertices.get();
+
+    lose_device(&state);
+    pollster::block_on(state.recover()).unwrap();
+    // The handle now holds a buffer on the new device, and the model's textures, buffers, bind
+    // groups and pipeline were created again without the app doing anything.
+    let restored = vertices.get();
+    assert!(!Arc::ptr_eq(&lost, &restored));
+    assert_eq!(restored.size(), 64);
+    drop((lost, restored, vertices));
+    assert_eq!(memory.total().current, total);
+    assert_eq!(state.render_to_image().unwrap(), before);
+}
@@ -0,0 +1,80 @@
+use wgpu_template::{MemoryCategory, MemoryTracker};
+
+mod common;
+
+#[allow(dead_code, clippy::module_inception)]
+#[path = "../examples/model/mod.rs"]
+mod model;
+
+#[allow(dead_code, non_snake_case)]
+#[path = "../examples/depthBuffer/mod.rs"]
+mod depthBuffer;
+
+#[test]
+fn entries_are_released_when_dropped() {
+    let memory = MemoryTracker::new();
+    let vertices = memory.track("vertices", MemoryCategory::Vertex, "VERTEX", 1000);
+    let indices = memory.track("indices", MemoryCategory::Index, "INDEX", 300);
+    assert_eq!(memory.total().current, 1300);
+    assert_eq!(memory.total().allocations, 2);
+
+    drop(vertices);
+    let more_vertices = memory.track("more vertices", MemoryCategory::Vertex, "VERTEX", 200);
+    let usage = memory.usage(MemoryCategory::Vertex);
+    assert_eq!(
+        (usage.current, usage.peak, usage.allocations),
+        (200, 1000, 1)
+    );
+    assert_eq!(memory.total().peak, 1300);
+    assert_eq!(memory.allocations()[0].label, "indices");
+
+    drop((indices, more_vertices));
+    assert_eq!(memory.total().current, 0);
+    assert_eq!(memory.usage(MemoryCategory::Uniform), Default::default());
+}
+
+#[test]
+fn model_resources_are_tracked_and_released() {
+    let Some(state) = common::state::<model::Example>() else {
+        return;
+    };
+    let memory = state.context().memory.clone();
+
+    for category in [
+        MemoryCategory::Vertex,
+        MemoryCategory::Index,
+        MemoryCategory::Uniform,
+        MemoryCategory::Texture,
+    ] {
+        assert!(memory.usage(category).current > 0, "no {category:?} memory");
+    }
+    // A 64x64 Depth32Float buffer.
+    assert_eq!(memory.usage(MemoryCategory::Depth).current, 64 * 64 * 4);
+
+    drop(state);
+    assert_eq!(memory.total().current, 0);
+    assert!(memory.allocations().is_empty());
+}
+
+#[test]
+fn example_buffers_and_textures_are_tracked() {
+    let Some(state) = common::state::<depthBuffer::Example>() else {
+        return;
+    };
+    let memory = state.context().memory.clone();
+
+    let labels: Vec<_> = memory.allocations().into_iter().map(|a| a.label).collect();
+    for label in [
+        "Vertex Buffer",
+        "Index Buffer",
+        "Instance Buffer",
+        "Camera Uniform Buffer",
+        "smile_png",
+        "depth_texture",
+    ] {
+        assert!(labels.iter().any(|l| l == label), "{label} is not tracked");
+    }
+
+    drop(state);
+    assert_eq!(memory.total().current, 0);
+}
@@ -0,0 +1,74 @@
+use wgpu_template::{Application, Frame, GpuContext, RendererConfig};
+
+mod common;
+
+struct Profiled;
+
+impl Application for Profiled {
+    fn init(_ctx: &GpuContext) -> wgpu_template::Result<Self> {
+        Ok(Self)
+    }
+
+    fn renderer_config() -> RendererConfig {
+        RendererConfig::default().gpu_profiling(true)
+    }
+
+    fn render(&mut self, frame: &mut Frame) {
+        frame.profiler.begin_scope("Frame", &mut frame.encoder);
+        frame
+            .encoder
+            .begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Clear Pass"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: frame.view,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                        store: wgpu::StoreOp::Store,
+                    },
+                })],
+                depth_stencil_attachment: None,
+                occlusion_query_set: None,
+                timestamp_writes: frame.profiler.render_pass_timestamps("Clear Pass"),
+            });
+        frame.profiler.end_scope(&mut frame.encoder);
+    }
+}
+
+#[test]
+fn scopes_are_resolved_and_exported() {
+    let Some(mut state) = common::state::<Profiled>() else {
+        return;
+    };
+    if !state.profiler().is_enabled() {
+        eprintln!("Skipping: the adapter does not support timestamp queries");
+        return;
+    }
+
+    for _ in 0..3 {
+        state.render().unwrap();
+    }
+    state.flush_profiler();
+
+    let frames: Vec<_> = state.profiler().frames().collect();
+    assert_eq!(frames.len(), 3);
+    let latest = state.profiler().latest().unwrap();
+    assert_eq!(latest.index, 3);
+    assert!(latest.duration("Clear Pass").is_some());
+    let pass = latest
+        .scopes
+        .iter()
+        .find(|s| s.label == "Clear Pass")
+        .unwrap();
+    assert_eq!(pass.depth, 1);
+
+    let trace: serde_json::Value = serde_json::from_str(&state.profiler().chrome_trace()).unwrap();
+    let events = trace["traceEvents"].as_array().unwrap();
+    assert_eq!(
+        events.len(),
+        frames.iter().map(|f| f.scopes.len()).sum::<usize>()
+    );
+    assert!(events
+        .iter()
+        .all(|e| e["ph"] == "X" && e["dur"].is_number()));
+}
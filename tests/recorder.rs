@@ -0,0 +1,92 @@
+use image::AnimationDecoder;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use wgpu_template::{Error, RecordFormat, Recorder, Recording, State};
+
+mod common;
+
+#[allow(dead_code)]
+#[path = "../examples/windows/mod.rs"]
+mod windows;
+
+const SIZE: u32 = 48;
+
+fn new_state() -> Option<State<windows::Example>> {
+    common::state_with(&common::config::<windows::Example>(), SIZE, SIZE)
+}
+
+fn output_path(name: &str) -> PathBuf {
+    let path = Path::new(env!("CARGO_TARGET_TMPDIR"))
+        .join("recordings")
+        .join(name);
+    let _ = std::fs::remove_dir_all(&path);
+    path
+}
+
+#[test]
+fn frames_are_taken_at_the_simulated_rate() {
+    let recording = Recording::new(output_path("frames"))
+        .fps(10.0)
+        .frames(3)
+        .start(Duration::from_millis(500));
+    assert_eq!(
+        RecordFormat::from_path(recording.path()),
+        RecordFormat::Pngs
+    );
+    let Some(mut state) = new_state() else {
+        return;
+    };
+    let dir = state.record(&recording).unwrap();
+
+    let names: Vec<_> = std::fs::read_dir(&dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+        .collect();
+    assert_eq!(names.len(), 3);
+
+    // The third frame shows the scene 0.5 s + 2 frames in, however long recording took.
+    let mut expected = new_state().unwrap();
+    expected.simulate(Duration::from_millis(700));
+    let frame = image::open(dir.join("frame_00002.png")).unwrap().to_rgba8();
+    assert_eq!(frame, expected.render_to_image().unwrap());
+}
+
+#[test]
+fn animations_are_written_as_gif_and_apng() {
+    let Some(mut state) = new_state() else {
+        return;
+    };
+    let gif = state
+        .record(&Recording::new(output_path("spin.gif")).fps(10.0).frames(4))
+        .unwrap();
+    let decoder = image::codecs::gif::GifDecoder::new(std::io::BufReader::new(
+        std::fs::File::open(gif).unwrap(),
+    ))
+    .unwrap();
+    let frames = decoder.into_frames().collect_frames().unwrap();
+    assert_eq!(frames.len(), 4);
+    assert_eq!(frames[0].buffer().dimensions(), (SIZE, SIZE));
+    assert_ne!(frames[0].buffer(), frames[3].buffer());
+
+    let apng = new_state()
+        .unwrap()
+        .record(&Recording::new(output_path("spin.png")).duration(Duration::from_millis(200)))
+        .unwrap();
+    let reader = png::Decoder::new(std::fs::File::open(apng).unwrap())
+        .read_info()
+        .unwrap();
+    let animation = reader.info().animation_control.unwrap();
+    // 0.2 s at the default 30 fps.
+    assert_eq!(animation.num_frames, 6);
+}
+
+#[test]
+fn a_frame_rate_that_is_not_positive_is_an_error() {
+    for fps in [0.0, -30.0, f64::NAN] {
+        let recording = Recording::new(output_path("never.gif")).fps(fps);
+        assert!(matches!(
+            Recorder::new(recording),
+            Err(Error::Record { .. })
+        ));
+    }
+}
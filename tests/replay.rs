@@ -0,0 +1,120 @@
+use image::RgbaImage;
+use std::path::Path;
+use std::time::Duration;
+use wgpu_template::{Application, InputEvent, InputRecording, State};
+use winit::dpi::PhysicalPosition;
+use winit::event::{DeviceId, WindowEvent};
+use winit::keyboard::KeyCode;
+
+mod common;
+
+#[allow(dead_code, non_snake_case)]
+#[path = "../examples/depthBuffer/mod.rs"]
+mod depthBuffer;
+
+#[allow(dead_code)]
+#[path = "../examples/triangle/mod.rs"]
+mod triangle;
+
+type Example = depthBuffer::Example;
+
+fn new_state() -> Option<State<Example>> {
+    common::state::<Example>()
+}
+
+fn key(key: KeyCode, pressed: bool) -> InputEvent {
+    InputEvent::Key { key, pressed }
+}
+
+/// Runs updates until `frame`, rendering a frame after every update, and returns the last one.
+fn run_to(state: &mut State<Example>, frame: u64) -> RgbaImage {
+    while state.frame_index() < frame {
+        state.update(Example::timestep());
+    }
+    state.render_to_image().unwrap()
+}
+
+#[test]
+fn a_replay_reproduces_the_recorded_frames() {
+    // The session: input arrives between updates, frames are taken along the way.
+    let Some(mut state) = new_state() else {
+        return;
+    };
+    state.record_input();
+    let mut recorded = Vec::new();
+    state.apply_input(key(KeyCode::KeyS, true));
+    recorded.push(run_to(&mut state, 3));
+    state.apply_input(key(KeyCode::KeyS, false));
+    state.apply_input(key(KeyCode::KeyA, true));
+    recorded.push(run_to(&mut state, 5));
+    state.apply_input(key(KeyCode::ArrowRight, true));
+    state.apply_input(key(KeyCode::KeyA, false));
+    recorded.push(run_to(&mut state, 9));
+    state.apply_input(key(KeyCode::ArrowRight, false));
+    recorded.push(run_to(&mut state, 10));
+    let recording = state.take_input_recording().unwrap();
+    assert_eq!(recording.frame_count(), 10);
+
+    let path = Path::new(env!("CARGO_TARGET_TMPDIR")).join("replay/session.ron");
+    recording.save(&path).unwrap();
+    let loaded = InputRecording::load(&path).unwrap();
+    assert_eq!(loaded, recording);
+
+    // Frames taken at the same updates are the same.
+    let mut replayed = new_state().unwrap();
+    replayed.replay_input(loaded);
+    let mut frames = Vec::new();
+    for frame in [3, 5, 9, 10] {
+        frames.push(run_to(&mut replayed, frame));
+    }
+    assert!(!replayed.is_replaying());
+    assert!(frames == recorded, "the replayed frames differ");
+    assert_ne!(frames[0], frames[3], "the input did not move the camera");
+}
+
+#[test]
+fn events_are_applied_before_their_update_however_updates_are_batched() {
+    let mut recording = InputRecording::new(Example::timestep());
+    recording.push(4, key(KeyCode::KeyD, false));
+    recording.push(2, key(KeyCode::KeyD, true));
+    assert_eq!(recording.events[0].frame, 2);
+    assert_eq!(recording.frame_count(), 5);
+
+    let Some(mut one_by_one) = new_state() else {
+        return;
+    };
+    one_by_one.replay_input(recording.clone());
+    let expected = run_to(&mut one_by_one, 8);
+
+    // `advance` runs several updates at once, each sees the events due.
+    let mut batched = new_state().unwrap();
+    batched.replay_input(recording);
+    batched.simulate(Example::timestep() * 8 + Duration::from_micros(100));
+    assert_eq!(batched.frame_index(), 8);
+    batched.update(Duration::ZERO);
+    assert_eq!(batched.render_to_image().unwrap(), expected);
+}
+
+#[test]
+fn live_input_does_not_reach_the_app_during_a_replay() {
+    // The triangle example colors its background after the cursor.
+    let cursor_moved = WindowEvent::CursorMoved {
+        // SAFETY: the id is only compared with others, never passed to the platform.
+        device_id: unsafe { DeviceId::dummy() },
+        position: PhysicalPosition::new(40.0, 20.0),
+    };
+    let Some(mut live) = common::state::<triangle::Example>() else {
+        return;
+    };
+    let unmoved = live.render_to_image().unwrap();
+    assert!(live.input(&cursor_moved));
+    assert_ne!(live.render_to_image().unwrap(), unmoved);
+
+    let mut recording = InputRecording::new(triangle::Example::timestep());
+    recording.push(5, key(KeyCode::KeyX, true));
+    let mut replayed = common::state::<triangle::Example>().unwrap();
+    replayed.replay_input(recording);
+    assert!(!replayed.input(&cursor_moved));
+    assert!(replayed.is_replaying());
+    assert_eq!(replayed.render_to_image().unwrap(), unmoved);
+}
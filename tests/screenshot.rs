@@ -0,0 +1,28 @@
+use std::path::Path;
+use wgpu_template::save_screenshot;
+
+mod common;
+
+#[allow(dead_code)]
+#[path = "../examples/triangle/mod.rs"]
+mod triangle;
+
+#[test]
+fn screenshots_are_saved_as_timestamped_pngs() {
+    let config = common::config::<triangle::Example>();
+    let Some(mut state) = common::state_with::<triangle::Example>(&config, 96, 64) else {
+        return;
+    };
+    let image = state.render_to_image().unwrap();
+
+    let dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join("screenshots");
+    let path = save_screenshot(&image, &dir).unwrap();
+
+    assert_eq!(path.parent(), Some(dir.as_path()));
+    let name = path.file_name().unwrap().to_str().unwrap();
+    assert!(
+        name.starts_with("screenshot-") && name.ends_with(".png"),
+        "{name}"
+    );
+    assert_eq!(image::open(&path).unwrap().to_rgba8(), image);
+}
@@ -0,0 +1,39 @@
+use std::time::Duration;
+use wgpu_template::{FrameStats, FrameTimings};
+
+fn frame(ms: u64) -> FrameTimings {
+    FrameTimings {
+        frame: Duration::from_millis(ms),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn summary_covers_the_rolling_window() {
+    let mut stats = FrameStats::new(100);
+    // The first frames leave the window.
+    for _ in 0..10 {
+        stats.record(frame(1000));
+    }
+    for ms in 1..=100 {
+        stats.record(frame(ms));
+    }
+
+    let summary = stats.frame();
+    assert_eq!(stats.frame_count(), 110);
+    assert_eq!(summary.min, Duration::from_millis(1));
+    assert_eq!(summary.max, Duration::from_millis(100));
+    assert_eq!(summary.average, Duration::from_micros(50_500));
+    assert_eq!(summary.p50, Duration::from_millis(51));
+    assert_eq!(summary.p95, Duration::from_millis(95));
+    assert_eq!(summary.p99, Duration::from_millis(99));
+    assert_eq!(stats.update().max, Duration::ZERO);
+}
+
+#[test]
+fn empty_stats_report_zero() {
+    let stats = FrameStats::default();
+    assert_eq!(stats.frame().average, Duration::ZERO);
+    assert_eq!(stats.fps(), 0.0);
+    assert!(stats.latest().is_none());
+}
@@ -0,0 +1,45 @@
+use std::time::Duration;
+use wgpu_template::FixedTimestep;
+
+const STEP: Duration = Duration::from_millis(10);
+
+#[test]
+fn elapsed_time_accumulates_into_whole_steps() {
+    let mut clock = FixedTimestep::new(STEP);
+    assert_eq!(clock.step(), STEP);
+    assert_eq!(clock.advance(Duration::from_millis(4)), 0);
+    assert_eq!(clock.advance(Duration::from_millis(4)), 0);
+    // 12 ms: one step, 2 ms carried over.
+    assert_eq!(clock.advance(Duration::from_millis(4)), 1);
+    assert_eq!(clock.advance(Duration::from_millis(28)), 3);
+    assert_eq!(clock.advance(Duration::ZERO), 0);
+}
+
+#[test]
+fn the_backlog_of_a_stall_is_dropped() {
+    let mut clock = FixedTimestep::new(STEP);
+    // A second-long stall runs the default 8 steps, not 100.
+    assert_eq!(clock.advance(Duration::from_secs(1)), 8);
+    assert_eq!(clock.alpha(), 0.0);
+    assert_eq!(clock.advance(Duration::from_millis(5)), 0);
+
+    let mut clock = FixedTimestep::new(STEP).with_max_steps(2);
+    assert_eq!(clock.advance(Duration::from_millis(35)), 2);
+    assert_eq!(clock.advance(Duration::from_millis(5)), 0);
+    // A limit of zero still runs one step.
+    let mut clock = FixedTimestep::new(STEP).with_max_steps(0);
+    assert_eq!(clock.advance(Duration::from_millis(35)), 1);
+}
+
+#[test]
+fn alpha_stays_within_a_step() {
+    let mut clock = FixedTimestep::new(STEP);
+    assert_eq!(clock.alpha(), 0.0);
+    clock.advance(Duration::from_millis(5));
+    assert!((clock.alpha() - 0.5).abs() < 1e-6, "{}", clock.alpha());
+    for elapsed in [3, 7, 10, 13, 99, 1000] {
+        clock.advance(Duration::from_millis(elapsed));
+        let alpha = clock.alpha();
+        assert!((0.0..1.0).contains(&alpha), "{alpha} after {elapsed} ms");
+    }
+}
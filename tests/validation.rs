@@ -0,0 +1,46 @@
+use wgpu_template::{Application, Error, Frame, GpuContext};
+
+mod common;
+
+/// Writes into a buffer that does not allow it, when told to.
+struct Invalid {
+    buffer: wgpu::Buffer,
+    write: bool,
+}
+
+impl Application for Invalid {
+    fn init(ctx: &GpuContext) -> wgpu_template::Result<Self> {
+        let buffer = ctx.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Read-only Buffer"),
+            size: 16,
+            usage: wgpu::BufferUsages::UNIFORM,
+            mapped_at_creation: false,
+        });
+        Ok(Self {
+            buffer,
+            write: false,
+        })
+    }
+
+    fn render(&mut self, frame: &mut Frame) {
+        if self.write {
+            frame.queue.write_buffer(&self.buffer, 0, &[0; 16]);
+        }
+    }
+}
+
+#[test]
+fn uncaptured_errors_fail_the_frame() {
+    let Some(mut state) = common::state::<Invalid>() else {
+        return;
+    };
+    state.render().unwrap();
+
+    state.app_mut().write = true;
+    assert!(matches!(state.render(), Err(Error::Gpu { .. })));
+    assert!(matches!(state.render_to_image(), Err(Error::Gpu { .. })));
+
+    // The error is reported once, the next valid frame renders.
+    state.app_mut().write = false;
+    state.render_to_image().unwrap();
+}